@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use glam::Vec3;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use wgpu::util::DeviceExt;
+use crate::mesh::{generate_icosphere, Vertex};
+use crate::propagator::{BodyInstance, GpuPropagator, KeplerElements, PropagatedBody, Propagation};
+
+/// planet_id de las rocas: su color sale de esta entrada de la tabla de superficies
+pub const ASTEROID_SURFACE: usize = 7;
+
+/// Formas distintas de roca; cada instancia usa una de ellas
+const ROCK_VARIANTS: u32 = 8;
+
+/// Cinturón de rocas en órbita alrededor del sol (en el origen)
+#[derive(Clone, Copy)]
+pub struct AsteroidBelt {
+    pub count: u32,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub max_inclination: f32, // radianes; da el grosor del cinturón
+    pub max_eccentricity: f32,
+    pub min_size: f32,
+    pub max_size: f32,
+    pub density: f32,         // G·masa por unidad de volumen (modo N-cuerpos)
+    pub seed: u64,
+}
+
+/// Cinturón en la GPU: formas de roca compartidas más un propagador que mueve
+/// cada roca en un compute shader y deja el resultado en el buffer de
+/// instancias que se dibuja. Las instancias van agrupadas por forma para
+/// dibujar cada una con un único draw_indexed.
+pub struct GpuAsteroids {
+    vb: wgpu::Buffer,
+    ib: wgpu::Buffer,
+    variants: Vec<Range<u32>>, // índices de cada forma
+    pub propagator: GpuPropagator,
+}
+
+impl GpuAsteroids {
+    pub fn new(device: &wgpu::Device, mu: f32, belt: &AsteroidBelt) -> Self {
+        let mut rng = StdRng::seed_from_u64(belt.seed);
+
+        // Todas las formas en un único par de buffers
+        let mut verts: Vec<Vertex> = Vec::new();
+        let mut inds: Vec<u32> = Vec::new();
+        let mut variants = Vec::new();
+        for _ in 0..ROCK_VARIANTS {
+            let (v, i) = generate_rock(&mut rng);
+            let base = verts.len() as u32;
+            let start = inds.len() as u32;
+            inds.extend(i.iter().map(|idx| idx + base));
+            verts.extend(v);
+            variants.push(start..inds.len() as u32);
+        }
+
+        let vb = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Asteroid VB"),
+            contents: bytemuck::cast_slice(&verts),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let ib = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Asteroid IB"),
+            contents: bytemuck::cast_slice(&inds),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        // Semiejes repartidos por área; cada roca sigue a Kepler alrededor
+        // del sol con su G·M `mu`
+        let (r0, r1) = (belt.inner_radius, belt.outer_radius);
+        let bodies: Vec<PropagatedBody> = (0..belt.count)
+            .map(|_| {
+                let axis = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0))
+                    .try_normalize()
+                    .unwrap_or(Vec3::Y);
+                let size = belt.min_size + rng.gen::<f32>().powi(3) * (belt.max_size - belt.min_size);
+                PropagatedBody {
+                    elements: KeplerElements {
+                        semi_major_axis: (r0 * r0 + rng.gen::<f32>() * (r1 * r1 - r0 * r0)).sqrt(),
+                        eccentricity: rng.gen::<f32>().powi(2) * belt.max_eccentricity,
+                        inclination: rng.gen_range(-1.0f32..1.0).powi(3) * belt.max_inclination,
+                        node: rng.gen_range(0.0..std::f32::consts::TAU),
+                        periapsis: rng.gen_range(0.0..std::f32::consts::TAU),
+                        mean_anomaly: rng.gen_range(0.0..std::f32::consts::TAU),
+                    },
+                    // Masa ~ volumen: solo las rocas grandes se notan entre sí
+                    mass: belt.density * size.powi(3),
+                    size,
+                    spin_axis: axis,
+                    spin_speed: rng.gen_range(-1.0..1.0),
+                }
+            })
+            .collect();
+
+        Self {
+            vb,
+            ib,
+            variants,
+            propagator: GpuPropagator::new(device, mu, Propagation::Kepler, &bodies),
+        }
+    }
+
+    /// Layouts de vértice del pipeline de asteroides: roca + instancias
+    pub fn buffer_layouts() -> [wgpu::VertexBufferLayout<'static>; 2] {
+        [Vertex::layout(), BodyInstance::layout()]
+    }
+
+    /// Dibuja el cinturón con el pipeline de asteroides ya fijado
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        let count = self.propagator.count();
+        if count == 0 {
+            return;
+        }
+        pass.set_vertex_buffer(0, self.vb.slice(..));
+        pass.set_vertex_buffer(1, self.propagator.instances().slice(..));
+        pass.set_index_buffer(self.ib.slice(..), wgpu::IndexFormat::Uint32);
+        let per_variant = count.div_ceil(ROCK_VARIANTS);
+        for (v, indices) in self.variants.iter().enumerate() {
+            let first = v as u32 * per_variant;
+            let last = (first + per_variant).min(count);
+            if first < last {
+                pass.draw_indexed(indices.clone(), 0, first..last);
+            }
+        }
+    }
+}
+
+/// Icosfera de radio ~1 deformada: un elipsoide aleatorio con bultos y
+/// cráteres. Las normales se recalculan sumando las de las caras, agrupando
+/// los vértices duplicados en la costura de la UV para no marcarla.
+pub fn generate_rock(rng: &mut StdRng) -> (Vec<Vertex>, Vec<u32>) {
+    let (mut verts, inds) = generate_icosphere(1.0, 2, ASTEROID_SURFACE as u32);
+
+    let scale = Vec3::new(1.0, rng.gen_range(0.6..1.0), rng.gen_range(0.5..0.9));
+    let bumps: Vec<(Vec3, f32, f32)> = (0..16)
+        .map(|_| {
+            let dir = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0))
+                .try_normalize()
+                .unwrap_or(Vec3::X);
+            // (dirección, altura: negativa = cráter, anchura angular)
+            (dir, rng.gen_range(-0.3..0.25), rng.gen_range(0.05..0.3))
+        })
+        .collect();
+
+    for v in &mut verts {
+        let n = Vec3::from(v.pos).normalize();
+        let r = 1.0 + bumps
+            .iter()
+            .map(|&(dir, height, width)| height * (-(1.0 - n.dot(dir)) / width).exp())
+            .sum::<f32>();
+        v.pos = (n * r * scale).to_array();
+    }
+
+    let key = |p: [f32; 3]| p.map(f32::to_bits);
+    let mut normals: HashMap<[u32; 3], Vec3> = HashMap::new();
+    for tri in inds.chunks(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(verts[tri[k] as usize].pos));
+        let face = (b - a).cross(c - a);
+        for &i in tri {
+            *normals.entry(key(verts[i as usize].pos)).or_insert(Vec3::ZERO) += face;
+        }
+    }
+    for v in &mut verts {
+        let n = normals[&key(v.pos)].normalize_or_zero();
+        v.normal = n.to_array();
+    }
+
+    (verts, inds)
+}
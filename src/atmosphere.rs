@@ -0,0 +1,111 @@
+use bytemuck::Zeroable;
+use glam::Vec3;
+use wgpu::util::DeviceExt;
+use crate::mesh::{generate_uv_sphere, Vertex};
+
+/// Atmósfera opcional de un cuerpo. Los coeficientes están expresados por
+/// unidad de grosor de la capa, así que el aspecto no depende del tamaño del
+/// planeta: con `rayleigh` ~ (0.2, 0.45, 1.1) se obtiene un cielo terrestre.
+#[derive(Clone, Copy)]
+pub struct Atmosphere {
+    pub body: usize,         // índice del cuerpo en planet_positions
+    pub height: f32,         // grosor de la capa en fracción del radio
+    pub rayleigh: [f32; 3],  // dispersión de Rayleigh por canal
+    pub mie: f32,            // dispersión de Mie (neblina, halo hacia el sol)
+    pub mie_g: f32,          // anisotropía de Mie (-1..1)
+    pub intensity: f32,      // intensidad de la luz del sol
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct AtmosphereParams {
+    center: [f32; 4],   // w = radio del planeta
+    rayleigh: [f32; 4], // w = radio de la atmósfera
+    mie: [f32; 4],      // coeficiente, g, intensidad, sin usar
+    sun_pos: [f32; 4],  // w sin usar
+}
+
+/// Capas de atmósfera en la GPU: una esfera unitaria compartida que se
+/// instancia una vez por atmósfera, escalada y centrada en el vertex shader.
+pub struct GpuAtmospheres {
+    vb: wgpu::Buffer,
+    ib: wgpu::Buffer,
+    icount: u32,
+    params_buf: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    count: u32,
+}
+
+impl GpuAtmospheres {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, atmospheres: &[Atmosphere]) -> Self {
+        let (verts, inds): (Vec<Vertex>, Vec<u32>) = generate_uv_sphere(1.0, 32, 48, 0);
+
+        let vb = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Atmosphere VB"),
+            contents: bytemuck::cast_slice(&verts),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let ib = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Atmosphere IB"),
+            contents: bytemuck::cast_slice(&inds),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        // Al menos un elemento para que el binding de storage sea válido
+        let params = vec![AtmosphereParams::zeroed(); atmospheres.len().max(1)];
+        let params_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Atmosphere Params"),
+            contents: bytemuck::cast_slice(&params),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buf.as_entire_binding(),
+            }],
+            label: Some("Atmosphere BG"),
+        });
+
+        Self {
+            vb,
+            ib,
+            icount: inds.len() as u32,
+            params_buf,
+            bind_group,
+            count: atmospheres.len() as u32,
+        }
+    }
+
+    /// Sube centros y radios actuales; el sol es el cuerpo 0
+    pub fn sync(&self, queue: &wgpu::Queue, atmospheres: &[Atmosphere], positions: &[(Vec3, f32)]) {
+        if atmospheres.is_empty() {
+            return;
+        }
+        let sun = positions[0].0;
+        let params: Vec<AtmosphereParams> = atmospheres
+            .iter()
+            .map(|a| {
+                let (center, radius) = positions[a.body];
+                AtmosphereParams {
+                    center: [center.x, center.y, center.z, radius],
+                    rayleigh: [a.rayleigh[0], a.rayleigh[1], a.rayleigh[2], radius * (1.0 + a.height)],
+                    mie: [a.mie, a.mie_g, a.intensity, 0.0],
+                    sun_pos: [sun.x, sun.y, sun.z, 1.0],
+                }
+            })
+            .collect();
+        queue.write_buffer(&self.params_buf, 0, bytemuck::cast_slice(&params));
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if self.count == 0 {
+            return;
+        }
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.vb.slice(..));
+        pass.set_index_buffer(self.ib.slice(..), wgpu::IndexFormat::Uint32);
+        pass.draw_indexed(0..self.icount, 0, 0..self.count);
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// Modo de cámara de una vista guardada
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewMode {
+    Orbit,
+    Fly,
+    Ship,
+}
+
+/// Vista guardada. La posición es relativa al cuerpo `focus` (el más
+/// cercano al guardarla), así que al recuperarla se sigue al cuerpo aunque
+/// se haya movido por su órbita.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub mode: ViewMode,
+    pub position: [f32; 3],    // ojo de la cámara
+    pub orientation: [f32; 4], // cuaternión, mirando hacia -Z
+    #[serde(default)]
+    pub focus: Option<usize>, // índice en planet_positions; sin él, posición absoluta
+    pub fov: f32,             // grados
+    pub distance: f32,        // al objetivo, en la vista orbital
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct BookmarkFile {
+    #[serde(default, rename = "bookmark")]
+    slots: BTreeMap<String, Bookmark>, // las claves de TOML son texto
+}
+
+/// Marcadores numerados que se guardan en un archivo TOML en cuanto cambian
+pub struct Bookmarks {
+    path: String,
+    slots: BTreeMap<u8, Bookmark>,
+}
+
+impl Bookmarks {
+    /// Lee los marcadores de `path`; si no existe se empieza sin ninguno
+    pub fn load(path: &str) -> Self {
+        let file = match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str::<BookmarkFile>(&text).unwrap_or_else(|e| {
+                println!("No se pudieron leer los marcadores de {} ({})", path, e);
+                BookmarkFile::default()
+            }),
+            Err(_) => BookmarkFile::default(),
+        };
+        let slots = file
+            .slots
+            .into_iter()
+            .filter_map(|(k, b)| k.parse().ok().map(|n| (n, b)))
+            .collect();
+        Self { path: path.to_string(), slots }
+    }
+
+    pub fn get(&self, slot: u8) -> Option<&Bookmark> {
+        self.slots.get(&slot)
+    }
+
+    pub fn store(&mut self, slot: u8, bookmark: Bookmark) {
+        self.slots.insert(slot, bookmark);
+        if let Err(e) = self.save() {
+            println!("No se pudieron guardar los marcadores en {} ({})", self.path, e);
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let file = BookmarkFile {
+            slots: self.slots.iter().map(|(k, b)| (k.to_string(), b.clone())).collect(),
+        };
+        let text = toml::to_string(&file).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, text).map_err(|e| e.to_string())
+    }
+}
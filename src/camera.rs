@@ -1,6 +1,22 @@
-use glam::{Mat4, Vec3, Vec2};
-use winit::keyboard::{Key, NamedKey};
-use winit::event::ElementState;
+use glam::{Mat4, Quat, Vec3, Vec2};
+use crate::input::{Action, AnalogControls};
+use crate::fly_camera::{FlyCamera, FlySettings};
+use crate::bookmarks::{Bookmark, ViewMode};
+use crate::camera_path::{CameraPath, Keyframe};
+
+/// Distancia mínima entre el objetivo de la cámara y la superficie de un cuerpo
+pub const CAMERA_COLLISION_RADIUS: f32 = 2.0;
+/// Duración en segundos del viaje al recuperar un marcador
+const TRANSITION_TIME: f32 = 1.5;
+/// La vista de nave tiene su propio campo de visión, más abierto
+const SHIP_FOV: f32 = 60.0;
+/// Índice de la nave en `planet_positions`
+const SHIP_BODY: usize = 2;
+/// A qué distancia del ojo se pone el punto de mira de una clave de
+/// recorrido fuera de la vista orbital
+const KEYFRAME_LOOK_DISTANCE: f32 = 10.0;
+/// Separación mínima en segundos entre dos claves del editor
+const MIN_KEYFRAME_GAP: f32 = 0.1;
 
 pub struct Camera {
     pub yaw: f32,
@@ -12,13 +28,42 @@ pub struct Camera {
     pub move_backward: bool,
     pub move_left: bool,
     pub move_right: bool,
+    pub move_up: bool,
+    pub move_down: bool,
+    pub boost: bool,
     
     pub ship_turn_left: bool,
     pub ship_turn_right: bool,
     pub ship_turn_up: bool,
     pub ship_turn_down: bool,
+    pub ship_roll_left: bool,
+    pub ship_roll_right: bool,
+    pub ship_thrust: bool,
+    pub analog: AnalogControls, // mando, si lo hay
     
     pub ship_view: bool,
+
+    /// Cámara de vuelo libre, independiente de la orbital
+    pub fly: FlyCamera,
+    pub fly_view: bool,
+
+    pub fov: f32, // grados, en las vistas orbital y de vuelo libre
+    transition: Option<Transition>,
+
+    /// Recorrido de cámara; mientras se reproduce manda sobre los demás modos
+    pub path: CameraPath,
+    path_time: Option<f32>,
+    /// Segundos desde la última clave, con el editor de recorrido abierto
+    editor: Option<f32>,
+}
+
+/// Vista desde la que se parte al saltar a un marcador; se interpola hacia
+/// la vista actual, que puede moverse mientras tanto (p. ej. con la nave)
+struct Transition {
+    eye: Vec3,
+    rotation: Quat,
+    fov: f32,
+    elapsed: f32,
 }
 
 pub struct CollisionSphere {
@@ -27,7 +72,7 @@ pub struct CollisionSphere {
 }
 
 impl Camera {
-    pub fn new() -> Self {
+    pub fn new(fly_settings: FlySettings, path: CameraPath) -> Self {
         Self {
             yaw: 0.0,
             pitch: -0.2,
@@ -38,23 +83,70 @@ impl Camera {
             move_backward: false,
             move_left: false,
             move_right: false,
+            move_up: false,
+            move_down: false,
+            boost: false,
             
             ship_turn_left: false,
             ship_turn_right: false,
             ship_turn_up: false,
             ship_turn_down: false,
+            ship_roll_left: false,
+            ship_roll_right: false,
+            ship_thrust: false,
+            analog: AnalogControls::default(),
             
             ship_view: false,
+
+            fly: FlyCamera::new(fly_settings),
+            fly_view: false,
+
+            fov: 45.0,
+            transition: None,
+
+            path,
+            path_time: None,
+            editor: None,
         }
     }
 
     pub fn update_from_input(&mut self, dt: f32, mouse_delta: Vec2, planets: &[CollisionSphere]) {
+        if let Some(t) = &mut self.transition {
+            t.elapsed += dt;
+            if t.elapsed >= TRANSITION_TIME {
+                self.transition = None;
+            }
+        }
+        if let Some(elapsed) = &mut self.editor {
+            *elapsed += dt;
+        }
+        if let Some(t) = &mut self.path_time {
+            *t += dt;
+            let done = *t >= self.path.duration();
+            self.follow_path();
+            if !done {
+                return;
+            }
+            self.stop_path();
+            println!("Recorrido terminado");
+        }
+
+        if self.fly_view {
+            let axis = |pos: bool, neg: bool| pos as i32 as f32 - neg as i32 as f32;
+            let movement = Vec3::new(
+                axis(self.move_right, self.move_left),
+                axis(self.move_up, self.move_down),
+                axis(self.move_forward, self.move_backward),
+            );
+            let roll = axis(self.ship_roll_right, self.ship_roll_left);
+            self.fly.update(dt, mouse_delta, movement, roll, self.boost, planets);
+            return;
+        }
+
         let sensitivity = 0.002;
         
-        // En vista de nave, el mouse controla la rotación de la nave
-        if self.ship_view {
-            self.ship_rot_from_mouse(dt, mouse_delta);
-        } else {
+        // En vista de nave, el mouse controla la rotación de la nave (ver update_player_ship)
+        if !self.ship_view {
             // En vista libre, el mouse controla la cámara
             self.yaw -= mouse_delta.x * sensitivity;
             self.pitch -= mouse_delta.y * sensitivity;
@@ -87,52 +179,77 @@ impl Camera {
         }
     }
 
-    fn ship_rot_from_mouse(&mut self, dt: f32, mouse_delta: Vec2) {
-        let mouse_sensitivity = 0.003;
-        // Usar ship_turn_* como flags temporales para aplicar rotación
-        // En su lugar, aplicaremos directamente desde mouse_delta
-        // Esto se hará en update_player_ship
-    }
-
-    pub fn process_key(&mut self, key: &Key, state: ElementState) {
-        let pressed = state == ElementState::Pressed;
-
-        match key {
-            Key::Character(s) => match s.as_str() {
-                "w" | "W" => self.move_forward = pressed,
-                "s" | "S" => self.move_backward = pressed,
-                "a" | "A" => self.move_left = pressed,
-                "d" | "D" => self.move_right = pressed,
-                "v" | "V" => if pressed { 
-                    self.ship_view = !self.ship_view;
-                    println!("Vista de nave: {}", if self.ship_view { "ACTIVADA" } else { "DESACTIVADA" });
-                },
-                _ => {}
-            },
-
-            Key::Named(NamedKey::ArrowUp) => {
-                self.ship_turn_up = pressed;
-            },
-            Key::Named(NamedKey::ArrowDown) => {
-                self.ship_turn_down = pressed;
+    pub fn process_action(&mut self, action: Action, pressed: bool) {
+        match action {
+            Action::MoveForward => self.move_forward = pressed,
+            Action::MoveBackward => self.move_backward = pressed,
+            Action::MoveLeft => self.move_left = pressed,
+            Action::MoveRight => self.move_right = pressed,
+            Action::MoveUp => self.move_up = pressed,
+            Action::MoveDown => self.move_down = pressed,
+            Action::Boost => self.boost = pressed,
+            Action::ToggleView if pressed => {
+                self.ship_view = !self.ship_view;
+                self.fly_view = false;
+                println!("Vista de nave: {}", if self.ship_view { "ACTIVADA" } else { "DESACTIVADA" });
             },
-            Key::Named(NamedKey::ArrowLeft) => {
-                self.ship_turn_left = pressed;
+            Action::ToggleFly if pressed => {
+                // Se sale desde donde estaba la cámara orbital
+                if !self.fly_view {
+                    self.fly.look_at(self.orbit_eye(), self.target);
+                }
+                self.fly_view = !self.fly_view;
+                self.ship_view = false;
+                println!("Vuelo libre: {}", if self.fly_view { "ACTIVADO" } else { "DESACTIVADO" });
             },
-            Key::Named(NamedKey::ArrowRight) => {
-                self.ship_turn_right = pressed;
+            Action::TurnUp => self.ship_turn_up = pressed,
+            Action::TurnDown => self.ship_turn_down = pressed,
+            Action::TurnLeft => self.ship_turn_left = pressed,
+            Action::TurnRight => self.ship_turn_right = pressed,
+            Action::RollLeft => self.ship_roll_left = pressed,
+            Action::RollRight => self.ship_roll_right = pressed,
+            Action::Thrust => self.ship_thrust = pressed,
+            Action::FovIn | Action::FovOut if pressed => {
+                let step = if action == Action::FovIn { -5.0 } else { 5.0 };
+                self.fov = (self.fov + step).clamp(20.0, 90.0);
+                println!("Campo de visión: {:.0}°", self.fov);
+            }
+            Action::PlayPath if pressed => {
+                if self.path_time.is_some() {
+                    self.stop_path();
+                    println!("Recorrido: DETENIDO");
+                } else if self.path.is_empty() {
+                    println!("No hay recorrido en {}", self.path.path());
+                } else {
+                    self.path_time = Some(0.0);
+                    self.transition = None;
+                    self.follow_path();
+                    println!("Recorrido: REPRODUCIENDO ({:.1} s)", self.path.duration());
+                }
+            }
+            Action::TogglePathEditor if pressed => {
+                if self.editor.take().is_some() {
+                    println!("Editor de recorrido: DESACTIVADO");
+                } else {
+                    // Se edita el recorrido grabado, no el que se estaba viendo
+                    self.stop_path();
+                    self.path.record();
+                    self.editor = Some(0.0);
+                    println!("Editor de recorrido: ACTIVADO ({}, {} claves)", self.path.path(), self.path.len());
+                }
+            }
+            Action::RemoveKeyframe if pressed && self.editor.is_some() => match self.path.pop() {
+                Some(k) => println!("Clave en t = {:.1} s quitada ({} en total)", k.time, self.path.len()),
+                None => println!("El recorrido está vacío"),
             },
-
             _ => {}
         }
     }
 
     fn check_collision(&self, new_target: Vec3, planets: &[CollisionSphere]) -> bool {
-        let camera_radius = 2.0;
-        
         for planet in planets {
             let dist_to_planet = new_target.distance(planet.center);
-            if dist_to_planet < (planet.radius + camera_radius) {
+            if dist_to_planet < (planet.radius + CAMERA_COLLISION_RADIUS) {
                 return true;
             }
         }
@@ -140,40 +257,227 @@ impl Camera {
         false
     }
 
-    pub fn view_proj(&self, aspect: f32) -> Mat4 {
-        let eye = Vec3::new(
+    /// Posición de la cámara en mundo según el modo activo
+    pub fn eye(&self, ship_pos: Vec3, ship_rot: (f32, f32, f32)) -> Vec3 {
+        self.current_view(ship_pos, ship_rot).0
+    }
+
+    /// Punto que se prueba contra los cuerpos: el objetivo en la vista
+    /// orbital (es lo que frena `check_collision`) y el ojo en las demás
+    pub fn collision_point(&self, ship_pos: Vec3, ship_rot: (f32, f32, f32)) -> Vec3 {
+        if self.view_mode() == ViewMode::Orbit {
+            self.target
+        } else {
+            self.eye(ship_pos, ship_rot)
+        }
+    }
+
+    fn orbit_eye(&self) -> Vec3 {
+        Vec3::new(
             self.target.x + self.radius * self.yaw.cos() * self.pitch.cos(),
             self.target.y + self.radius * self.pitch.sin(),
             self.target.z + self.radius * self.yaw.sin() * self.pitch.cos(),
-        );
-
-        let view = Mat4::look_at_rh(eye, self.target, Vec3::Y);
-        let proj = Mat4::perspective_rh(45_f32.to_radians(), aspect, 0.1, 5000.0);
+        )
+    }
 
-        proj * view
+    fn ship_eye(ship_pos: Vec3) -> Vec3 {
+        ship_pos + Vec3::new(0.0, 0.5, 0.0)
     }
 
-    pub fn view_proj_from_ship(&self, ship_pos: Vec3, ship_rot: (f32, f32), aspect: f32) -> Mat4 {
-        // Vista en primera persona desde la nave
-        let eye = ship_pos + Vec3::new(0.0, 0.5, 0.0);
-        
-        // Dirección en la que apunta la nave (basada en rotación)
-        let forward = Vec3::new(
+    /// Dirección en la que apunta la nave
+    pub fn ship_forward(ship_rot: (f32, f32, f32)) -> Vec3 {
+        Vec3::new(
             ship_rot.0.cos() * ship_rot.1.cos(),
             ship_rot.1.sin(),
             ship_rot.0.sin() * ship_rot.1.cos(),
-        );
-        
-        let target = eye + forward * 1000.0;
-        
-        let view = Mat4::look_at_rh(eye, target, Vec3::Y);
-        let proj = Mat4::perspective_rh(60_f32.to_radians(), aspect, 0.1, 10000.0);
+        )
+    }
+
+    fn view_mode(&self) -> ViewMode {
+        // El recorrido mueve la cámara de vuelo libre (ver `follow_path`), así
+        // que un marcador guardado durante un recorrido queda como vuelo libre
+        if self.fly_view || self.path_time.is_some() {
+            ViewMode::Fly
+        } else if self.ship_view {
+            ViewMode::Ship
+        } else {
+            ViewMode::Orbit
+        }
+    }
+
+    /// Ojo, orientación (mirando hacia -Z) y campo de visión del modo activo
+    fn mode_view(&self, ship_pos: Vec3, ship_rot: (f32, f32, f32)) -> (Vec3, Quat, f32) {
+        match self.view_mode() {
+            ViewMode::Fly => (self.fly.position, self.fly.orientation, self.fov),
+            ViewMode::Ship => {
+                // Vista en primera persona; el alabeo gira el horizonte
+                // alrededor de la dirección de vuelo
+                let forward = Self::ship_forward(ship_rot);
+                let up = Quat::from_axis_angle(forward, ship_rot.2) * Vec3::Y;
+                (Self::ship_eye(ship_pos), look_rotation(forward, up), SHIP_FOV)
+            }
+            ViewMode::Orbit => {
+                let eye = self.orbit_eye();
+                (eye, look_rotation(self.target - eye, Vec3::Y), self.fov)
+            }
+        }
+    }
+
+    /// Como `mode_view`, pero a mitad de camino si hay una transición
+    fn current_view(&self, ship_pos: Vec3, ship_rot: (f32, f32, f32)) -> (Vec3, Quat, f32) {
+        let (eye, rotation, fov) = self.mode_view(ship_pos, ship_rot);
+        let Some(t) = &self.transition else {
+            return (eye, rotation, fov);
+        };
+        let x = (t.elapsed / TRANSITION_TIME).clamp(0.0, 1.0);
+        let k = x * x * (3.0 - 2.0 * x);
+        (t.eye.lerp(eye, k), t.rotation.slerp(rotation, k), t.fov + (fov - t.fov) * k)
+    }
+
+    pub fn view_proj(&self, ship_pos: Vec3, ship_rot: (f32, f32, f32), aspect: f32) -> Mat4 {
+        let (eye, rotation, fov) = self.current_view(ship_pos, ship_rot);
+        let far = if self.ship_view { 10000.0 } else { 5000.0 };
+
+        let view = Mat4::look_to_rh(eye, rotation * Vec3::NEG_Z, rotation * Vec3::Y);
+        let proj = Mat4::perspective_rh(fov.to_radians(), aspect, 0.1, far);
 
         proj * view
     }
 
-    pub fn update_player_ship(&self, dt: f32, mouse_delta: Vec2, ship_pos: &mut Vec3, ship_rot: &mut (f32, f32)) {
-        let ship_speed = 40.0;
+    /// Marcador con la vista actual, relativa al cuerpo en el que se centra
+    pub fn capture(&self, ship_pos: Vec3, ship_rot: (f32, f32, f32), bodies: &[(Vec3, f32)]) -> Bookmark {
+        let mode = self.view_mode();
+        let (eye, rotation, fov) = self.mode_view(ship_pos, ship_rot);
+        let nearest = |p: Vec3| {
+            bodies
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| (p.distance(a.0) - a.1).total_cmp(&(p.distance(b.0) - b.1)))
+                .map(|(i, _)| i)
+        };
+        let focus = match mode {
+            ViewMode::Orbit => nearest(self.target),
+            ViewMode::Fly => nearest(eye),
+            ViewMode::Ship => Some(SHIP_BODY),
+        };
+        let anchor = focus.map_or(Vec3::ZERO, |i| bodies[i].0);
+        Bookmark {
+            mode,
+            position: (eye - anchor).to_array(),
+            orientation: rotation.to_array(),
+            focus,
+            fov,
+            distance: self.radius,
+        }
+    }
+
+    /// Lleva la cámara al marcador con una transición suave. En la vista de
+    /// nave solo se recupera la orientación: la nave no se teletransporta.
+    pub fn recall(&mut self, bookmark: &Bookmark, ship_pos: Vec3, ship_rot: &mut (f32, f32, f32), bodies: &[(Vec3, f32)]) {
+        let (from_eye, from_rotation, from_fov) = self.current_view(ship_pos, *ship_rot);
+        // Si no, el recorrido seguiría mandando sobre la vista
+        self.stop_path();
+
+        let anchor = bookmark.focus.and_then(|i| bodies.get(i)).map_or(Vec3::ZERO, |b| b.0);
+        let eye = anchor + Vec3::from(bookmark.position);
+        let rotation = Quat::from_array(bookmark.orientation).normalize();
+        let forward = rotation * Vec3::NEG_Z;
+
+        self.fly_view = bookmark.mode == ViewMode::Fly;
+        self.ship_view = bookmark.mode == ViewMode::Ship;
+        match bookmark.mode {
+            ViewMode::Orbit => {
+                self.radius = bookmark.distance;
+                self.target = eye + forward * self.radius;
+                self.pitch = (-forward.y).asin().clamp(-1.4, 1.4);
+                self.yaw = (-forward.z).atan2(-forward.x);
+                self.fov = bookmark.fov;
+            }
+            ViewMode::Fly => {
+                self.fly.position = eye;
+                self.fly.orientation = rotation;
+                self.fov = bookmark.fov;
+            }
+            ViewMode::Ship => {
+                ship_rot.0 = forward.z.atan2(forward.x);
+                ship_rot.1 = forward.y.clamp(-1.0, 1.0).asin();
+                // Alabeo: ángulo entre el "arriba" sin alabear y el guardado
+                let level = look_rotation(forward, Vec3::Y) * Vec3::Y;
+                let up = rotation * Vec3::Y;
+                ship_rot.2 = level.cross(up).dot(forward).atan2(level.dot(up)).rem_euclid(std::f32::consts::TAU);
+            }
+        }
+
+        self.transition = Some(Transition {
+            eye: from_eye,
+            rotation: from_rotation,
+            fov: from_fov,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Pone la cámara de vuelo libre en el punto del recorrido que toca
+    fn follow_path(&mut self) {
+        let Some((eye, look_at, fov)) = self.path_time.and_then(|t| self.path.sample(t)) else {
+            return;
+        };
+        self.fly.position = eye;
+        // Mirando a su propia posición (o en vertical) la orientación no está
+        // definida: se mantiene la anterior
+        let forward = look_at - eye;
+        if forward.cross(Vec3::Y).length_squared() > 1e-8 {
+            self.fly.orientation = look_rotation(forward, Vec3::Y);
+        }
+        self.fov = fov;
+    }
+
+    /// Deja la cámara en vuelo libre donde iba el recorrido
+    fn stop_path(&mut self) {
+        if self.path_time.take().is_some() {
+            self.fly_view = true;
+            self.ship_view = false;
+        }
+    }
+
+    pub fn path_playing(&self) -> bool {
+        self.path_time.is_some()
+    }
+
+    pub fn path_editing(&self) -> bool {
+        self.editor.is_some()
+    }
+
+    /// Con el editor abierto, añade al recorrido una clave con la vista
+    /// actual. Su tiempo es el que pasó desde la clave anterior, así que el
+    /// ritmo de la reproducción es el de la grabación.
+    pub fn add_keyframe(&mut self, ship_pos: Vec3, ship_rot: (f32, f32, f32)) {
+        let Some(elapsed) = self.editor else {
+            println!("El editor de recorrido no está activo");
+            return;
+        };
+        let (eye, rotation, fov) = self.current_view(ship_pos, ship_rot);
+        let look_distance = if self.view_mode() == ViewMode::Orbit { self.radius } else { KEYFRAME_LOOK_DISTANCE };
+        let time = if self.path.is_empty() { 0.0 } else { self.path.duration() + elapsed.max(MIN_KEYFRAME_GAP) };
+        self.path.push(Keyframe {
+            time,
+            position: eye.to_array(),
+            look_at: (eye + rotation * Vec3::NEG_Z * look_distance).to_array(),
+            fov,
+        });
+        self.editor = Some(0.0);
+        println!("Clave {} en t = {:.1} s", self.path.len(), time);
+    }
+
+    /// Potencia del propulsor, 0..1: la tecla lo enciende del todo y el
+    /// gatillo del mando lo regula
+    pub fn thrust_level(&self) -> f32 {
+        if self.ship_thrust { 1.0 } else { self.analog.thrust.clamp(0.0, 1.0) }
+    }
+
+    /// Mueve la nave y devuelve su velocidad
+    pub fn update_player_ship(&self, dt: f32, mouse_delta: Vec2, ship_pos: &mut Vec3, ship_rot: &mut (f32, f32, f32)) -> Vec3 {
+        // Con el propulsor encendido la nave acelera hasta el doble
+        let ship_speed = 40.0 * (1.0 + self.thrust_level());
         let rotation_speed = 2.0;
         let mouse_sensitivity = 0.003;
 
@@ -198,12 +502,27 @@ impl Camera {
             }
         }
 
-        let forward = Vec3::new(
-            ship_rot.0.cos() * ship_rot.1.cos(),
-            ship_rot.1.sin(),
-            ship_rot.0.sin() * ship_rot.1.cos(),
-        );
+        // El mando vale en las dos vistas
+        ship_rot.0 -= self.analog.yaw * rotation_speed * dt;
+        ship_rot.1 = (ship_rot.1 + self.analog.pitch * rotation_speed * dt)
+            .clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
 
-        *ship_pos += forward * ship_speed * dt;
+        let mut roll = self.analog.roll;
+        if self.ship_roll_left {
+            roll -= 1.0;
+        }
+        if self.ship_roll_right {
+            roll += 1.0;
+        }
+        ship_rot.2 = (ship_rot.2 + roll * rotation_speed * dt).rem_euclid(std::f32::consts::TAU);
+
+        let velocity = Self::ship_forward(*ship_rot) * ship_speed;
+        *ship_pos += velocity * dt;
+        velocity
     }
-}
\ No newline at end of file
+}
+
+/// Orientación de una cámara que mira hacia `forward` (su -Z local)
+fn look_rotation(forward: Vec3, up: Vec3) -> Quat {
+    Quat::from_mat4(&Mat4::look_to_rh(Vec3::ZERO, forward, up).inverse()).normalize()
+}
@@ -0,0 +1,241 @@
+use std::ops::{Add, Mul};
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+/// Punto de paso de un recorrido de cámara, en coordenadas de mundo
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub time: f32, // segundos desde el inicio del recorrido
+    pub position: [f32; 3],
+    pub look_at: [f32; 3],
+    pub fov: f32, // grados
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PathFile {
+    #[serde(default, rename = "keyframe")]
+    keyframes: Vec<Keyframe>,
+}
+
+/// Recorrido de cámara: una spline Catmull-Rom que pasa por las claves en
+/// sus tiempos. La vista depende solo del tiempo de reproducción, así que
+/// dos reproducciones con los mismos pasos dan exactamente la misma imagen.
+/// El editor nunca escribe en el archivo del que se leyó el recorrido, sino
+/// en `output` (ver `record`).
+pub struct CameraPath {
+    path: String,   // de dónde salen las claves
+    output: String, // dónde guarda el editor
+    keyframes: Vec<Keyframe>, // ordenadas por tiempo, sin tiempos repetidos
+}
+
+impl CameraPath {
+    /// Lee el recorrido de `path`; el editor guardará en `output`
+    pub fn load(path: &str, output: &str) -> Self {
+        Self { path: path.to_string(), output: output.to_string(), keyframes: Self::read(path) }
+    }
+
+    /// Claves de `path`; si no existe no hay ninguna
+    fn read(path: &str) -> Vec<Keyframe> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str::<PathFile>(&text)
+                .map_err(|e| e.to_string())
+                .and_then(|file| Self::validate(file.keyframes))
+                .unwrap_or_else(|e| {
+                    println!("No se pudo leer el recorrido de {} ({})", path, e);
+                    Vec::new()
+                }),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Pasa a editar el recorrido de `output`, con lo que ya tuviera
+    /// grabado: el recorrido cargado (p. ej. el de ejemplo) no se toca
+    pub fn record(&mut self) {
+        if self.path != self.output {
+            self.keyframes = Self::read(&self.output);
+            self.path = self.output.clone();
+        }
+    }
+
+    fn validate(mut keyframes: Vec<Keyframe>) -> Result<Vec<Keyframe>, String> {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        if let Some(w) = keyframes.windows(2).find(|w| w[1].time - w[0].time < 1e-3) {
+            return Err(format!("dos claves en t = {}", w[1].time));
+        }
+        Ok(keyframes)
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Añade una clave al final y guarda el archivo (después de `record`)
+    pub fn push(&mut self, keyframe: Keyframe) {
+        self.record();
+        self.keyframes.push(keyframe);
+        self.save();
+    }
+
+    /// Quita la última clave y guarda el archivo (después de `record`)
+    pub fn pop(&mut self) -> Option<Keyframe> {
+        self.record();
+        let keyframe = self.keyframes.pop()?;
+        self.save();
+        Some(keyframe)
+    }
+
+    fn save(&self) {
+        let file = PathFile { keyframes: self.keyframes.clone() };
+        let result = toml::to_string(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|text| std::fs::write(&self.path, text).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            println!("No se pudo guardar el recorrido en {} ({})", self.path, e);
+        }
+    }
+
+    /// Ojo, punto al que se mira y campo de visión en el instante `t`
+    pub fn sample(&self, t: f32) -> Option<(Vec3, Vec3, f32)> {
+        let keys = &self.keyframes;
+        let last = keys.len().checked_sub(1)?;
+        // Fuera del recorrido la cámara se queda en el extremo
+        if last == 0 || t <= keys[0].time || t >= keys[last].time {
+            let k = if t <= keys[0].time { &keys[0] } else { &keys[last] };
+            return Some((k.position.into(), k.look_at.into(), k.fov));
+        }
+        // Tramo [i, i + 1] que contiene t
+        let i = keys.partition_point(|k| k.time <= t) - 1;
+
+        // En los extremos se refleja la clave vecina para tener los cuatro
+        // puntos de control
+        let key = |j: usize| {
+            let k = &keys[j];
+            (k.time, Vec3::from(k.position), Vec3::from(k.look_at), k.fov)
+        };
+        let mirror = |inner: usize, outer: usize| {
+            let (ta, pa, la, fa) = key(inner);
+            let (tb, pb, lb, fb) = key(outer);
+            (2.0 * ta - tb, 2.0 * pa - pb, 2.0 * la - lb, 2.0 * fa - fb)
+        };
+        let k0 = if i == 0 { mirror(0, 1) } else { key(i - 1) };
+        let k1 = key(i);
+        let k2 = key(i + 1);
+        let k3 = if i + 1 == last { mirror(last, last - 1) } else { key(i + 2) };
+
+        let times = [k0.0, k1.0, k2.0, k3.0];
+        Some((
+            catmull_rom([k0.1, k1.1, k2.1, k3.1], times, t),
+            catmull_rom([k0.2, k1.2, k2.2, k3.2], times, t),
+            catmull_rom([k0.3, k1.3, k2.3, k3.3], times, t),
+        ))
+    }
+}
+
+/// Catmull-Rom no uniforme (Barry-Goldman) entre `p[1]` y `p[2]`, con los
+/// nudos en los tiempos de las claves: con claves a distinto ritmo la
+/// velocidad no salta al pasar por ellas
+fn catmull_rom<T>(p: [T; 4], k: [f32; 4], t: f32) -> T
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    let lerp = |a: T, b: T, ta: f32, tb: f32| a * ((tb - t) / (tb - ta)) + b * ((t - ta) / (tb - ta));
+    let a1 = lerp(p[0], p[1], k[0], k[1]);
+    let a2 = lerp(p[1], p[2], k[1], k[2]);
+    let a3 = lerp(p[2], p[3], k[2], k[3]);
+    let b1 = lerp(a1, a2, k[0], k[2]);
+    let b2 = lerp(a2, a3, k[1], k[3]);
+    lerp(b1, b2, k[1], k[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(keys: &[(f32, [f32; 3])]) -> CameraPath {
+        let keyframes = keys
+            .iter()
+            .map(|&(time, position)| Keyframe { time, position, look_at: [0.0; 3], fov: 45.0 + time })
+            .collect();
+        CameraPath { path: String::new(), output: String::new(), keyframes: CameraPath::validate(keyframes).unwrap() }
+    }
+
+    #[test]
+    fn passes_through_keyframes() {
+        let p = path(&[(0.0, [0.0, 0.0, 0.0]), (1.0, [4.0, 1.0, 0.0]), (3.5, [2.0, -3.0, 5.0]), (4.0, [-1.0, 0.0, 2.0])]);
+        for k in &p.keyframes {
+            let (eye, _, fov) = p.sample(k.time).unwrap();
+            assert!(eye.distance(k.position.into()) < 1e-4, "t = {}: {}", k.time, eye);
+            assert!((fov - k.fov).abs() < 1e-4);
+        }
+        // Fuera del recorrido se queda en los extremos
+        assert_eq!(p.sample(-1.0).unwrap().0, Vec3::ZERO);
+        assert_eq!(p.sample(10.0).unwrap().0, Vec3::new(-1.0, 0.0, 2.0));
+    }
+
+    #[test]
+    fn deterministic() {
+        let p = path(&[(0.0, [0.0, 0.0, 0.0]), (1.3, [4.0, 1.0, 0.0]), (2.0, [2.0, -3.0, 5.0])]);
+        for i in 0..=40 {
+            let t = i as f32 * 0.05;
+            let (a, b) = (p.sample(t).unwrap(), p.sample(t).unwrap());
+            assert_eq!(a.0.to_array().map(f32::to_bits), b.0.to_array().map(f32::to_bits));
+        }
+    }
+
+    #[test]
+    fn uniform_motion_stays_uniform() {
+        // Claves sobre una recta a velocidad constante, con tiempos irregulares
+        let v = Vec3::new(1.0, -2.0, 0.5);
+        let times = [0.0, 0.4, 1.5, 1.7, 3.0];
+        let keys: Vec<_> = times.iter().map(|&t| (t, (v * t).to_array())).collect();
+        let p = path(&keys);
+        for i in 0..=30 {
+            let t = i as f32 * 0.1;
+            assert!(p.sample(t).unwrap().0.distance(v * t) < 1e-4, "t = {}", t);
+        }
+    }
+
+    #[test]
+    fn rejects_repeated_times() {
+        let k = Keyframe { time: 1.0, position: [0.0; 3], look_at: [0.0; 3], fov: 45.0 };
+        assert!(CameraPath::validate(vec![k, Keyframe { time: 1.0005, ..k }]).is_err());
+        assert!(CameraPath::validate(vec![Keyframe { time: 2.0, ..k }, k]).is_ok());
+    }
+
+    #[test]
+    fn editor_leaves_source_alone() {
+        let source = "src/scenes/recorrido.toml";
+        let before = std::fs::read_to_string(source).unwrap();
+        let output = std::env::temp_dir().join("solarsoft_path_test.toml");
+        let output = output.to_str().unwrap();
+        std::fs::remove_file(output).ok();
+
+        let mut p = CameraPath::load(source, output);
+        assert!(p.len() > 1);
+        p.record();
+        assert!(p.is_empty());
+        p.push(Keyframe { time: 0.0, position: [1.0, 2.0, 3.0], look_at: [0.0; 3], fov: 50.0 });
+        assert_eq!(CameraPath::load(output, output).len(), 1);
+        assert_eq!(std::fs::read_to_string(source).unwrap(), before);
+        std::fs::remove_file(output).ok();
+    }
+
+    #[test]
+    fn catmull_rom_scalar() {
+        let k = [0.0, 1.0, 2.0, 3.0];
+        assert_eq!(catmull_rom([5.0, 5.0, 5.0, 5.0], k, 1.5), 5.0);
+        assert!((catmull_rom([0.0, 1.0, 2.0, 3.0], k, 1.25) - 1.25).abs() < 1e-6);
+    }
+}
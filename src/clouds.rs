@@ -0,0 +1,111 @@
+use glam::Vec3;
+use wgpu::util::DeviceExt;
+use crate::mesh::{generate_uv_sphere, Vertex};
+use crate::surface::Surface;
+
+/// Una capa de nubes: la esfera unitaria centrada y escalada sobre su cuerpo
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CloudInstance {
+    center: [f32; 4], // w = radio de la capa
+    body: u32,        // planet_id, para leer la superficie en el shader
+}
+
+impl CloudInstance {
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<CloudInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                4 => Float32x4,  // center + radio
+                5 => Uint32      // body
+            ])),
+        }
+    }
+
+    /// Layouts de vértice del pipeline de nubes: esfera unitaria + instancias
+    pub fn buffer_layouts() -> [wgpu::VertexBufferLayout<'static>; 2] {
+        [Vertex::layout(), CloudInstance::layout()]
+    }
+}
+
+/// Capas de nubes de los cuerpos con `clouds > 0` en su superficie. La
+/// cobertura, la altura y la velocidad se leen de la tabla de superficies,
+/// que es la misma que usa el suelo para calcular la sombra de las nubes.
+pub struct GpuClouds {
+    vb: wgpu::Buffer,
+    ib: wgpu::Buffer,
+    icount: u32,
+    instance_buf: wgpu::Buffer,
+    bodies: Vec<(usize, f32)>, // (cuerpo, altura de la capa)
+}
+
+impl GpuClouds {
+    /// `body_count` es el número de cuerpos con posición en la escena; las
+    /// superficies de otros (p. ej. la de los asteroides) no llevan capa
+    pub fn new(device: &wgpu::Device, surfaces: &[Surface], body_count: usize) -> Self {
+        let (verts, inds) = generate_uv_sphere(1.0, 48, 96, 0);
+
+        let vb = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Clouds VB"),
+            contents: bytemuck::cast_slice(&verts),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let ib = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Clouds IB"),
+            contents: bytemuck::cast_slice(&inds),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let bodies: Vec<(usize, f32)> = surfaces
+            .iter()
+            .filter(|s| s.clouds > 0.0 && s.body < body_count)
+            .map(|s| (s.body, s.cloud_height))
+            .collect();
+
+        let instance_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Cloud Instances"),
+            size: (bodies.len().max(1) * std::mem::size_of::<CloudInstance>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            vb,
+            ib,
+            icount: inds.len() as u32,
+            instance_buf,
+            bodies,
+        }
+    }
+
+    /// Sigue a cada cuerpo en su órbita
+    pub fn sync(&self, queue: &wgpu::Queue, positions: &[(Vec3, f32)]) {
+        if self.bodies.is_empty() {
+            return;
+        }
+        let instances: Vec<CloudInstance> = self
+            .bodies
+            .iter()
+            .map(|&(body, height)| {
+                let (center, radius) = positions[body];
+                CloudInstance {
+                    center: [center.x, center.y, center.z, radius * (1.0 + height)],
+                    body: body as u32,
+                }
+            })
+            .collect();
+        queue.write_buffer(&self.instance_buf, 0, bytemuck::cast_slice(&instances));
+    }
+
+    /// Dibuja las capas con el pipeline de nubes ya fijado
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if self.bodies.is_empty() {
+            return;
+        }
+        pass.set_vertex_buffer(0, self.vb.slice(..));
+        pass.set_vertex_buffer(1, self.instance_buf.slice(..));
+        pass.set_index_buffer(self.ib.slice(..), wgpu::IndexFormat::Uint32);
+        pass.draw_indexed(0..self.icount, 0, 0..self.bodies.len() as u32);
+    }
+}
@@ -0,0 +1,154 @@
+use glam::{Quat, Vec3};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use wgpu::util::DeviceExt;
+use crate::asteroids::generate_rock;
+use crate::propagator::{BodyInstance, KeplerElements};
+
+/// Partículas de cada cola; ION_PARTICLES debe coincidir con shader_comet.wgsl
+const ION_PARTICLES: u32 = 160;
+const DUST_PARTICLES: u32 = 400;
+/// Vértices por cometa: la coma más las dos colas, 6 por partícula
+const TAIL_VERTICES: u32 = (1 + ION_PARTICLES + DUST_PARTICLES) * 6;
+
+/// Cometa en órbita excéntrica alrededor del sol. Las colas crecen y brillan
+/// más cuanto más cerca pasa del sol.
+#[derive(Clone, Copy)]
+pub struct Comet {
+    pub elements: KeplerElements,
+    pub size: f32,            // radio del núcleo
+    pub tail_length: f32,     // longitud de las colas con actividad máxima
+    pub activity_radius: f32, // distancia al sol por debajo de la cual la actividad es máxima
+}
+
+/// Estado de un cometa para dibujar sus colas
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct CometInstance {
+    nucleus: [f32; 4],  // w = radio del núcleo
+    anti_sun: [f32; 4], // w = longitud de las colas
+    motion: [f32; 4],   // dirección del movimiento, w = actividad (0..1)
+}
+
+impl CometInstance {
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<CometInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                0 => Float32x4,  // núcleo
+                1 => Float32x4,  // dirección opuesta al sol + longitud
+                2 => Float32x4   // movimiento + actividad
+            ])),
+        }
+    }
+}
+
+/// Cometas en la GPU. La órbita se evalúa en la CPU (son pocos); el núcleo
+/// se dibuja como una roca más con el pipeline de asteroides y las colas
+/// como chorros de partículas aditivas que el vertex shader genera a partir
+/// del estado de cada cometa, sin buffers de partículas.
+pub struct GpuComets {
+    comets: Vec<Comet>,
+    mu: f32,
+    vb: wgpu::Buffer,
+    ib: wgpu::Buffer,
+    icount: u32,
+    nucleus_buf: wgpu::Buffer,
+    tail_buf: wgpu::Buffer,
+}
+
+impl GpuComets {
+    pub fn new(device: &wgpu::Device, mu: f32, comets: &[Comet]) -> Self {
+        let (verts, inds) = generate_rock(&mut StdRng::seed_from_u64(7));
+
+        let vb = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Comet VB"),
+            contents: bytemuck::cast_slice(&verts),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let ib = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Comet IB"),
+            contents: bytemuck::cast_slice(&inds),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let count = comets.len().max(1);
+        let nucleus_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Comet Nuclei"),
+            size: (count * std::mem::size_of::<BodyInstance>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let tail_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Comet Tails"),
+            size: (count * std::mem::size_of::<CometInstance>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            comets: comets.to_vec(),
+            mu,
+            vb,
+            ib,
+            icount: inds.len() as u32,
+            nucleus_buf,
+            tail_buf,
+        }
+    }
+
+    /// Layout de vértice del pipeline de colas: una instancia por cometa
+    pub fn tail_layouts() -> [wgpu::VertexBufferLayout<'static>; 1] {
+        [CometInstance::layout()]
+    }
+
+    /// Mueve los cometas a su posición en `time` alrededor del sol en `sun`
+    pub fn update(&self, queue: &wgpu::Queue, time: f32, sun: Vec3) {
+        if self.comets.is_empty() {
+            return;
+        }
+        let mut nuclei = Vec::with_capacity(self.comets.len());
+        let mut tails = Vec::with_capacity(self.comets.len());
+        for (i, comet) in self.comets.iter().enumerate() {
+            let (rel, vel) = comet.elements.state_vectors(self.mu, time);
+            let pos = sun + rel;
+            let r = rel.length().max(1e-3);
+            let activity = (comet.activity_radius / r).powi(2).min(1.0);
+
+            // Giro lento del núcleo, distinto para cada cometa
+            let spin = Quat::from_axis_angle(Vec3::new(0.3, 1.0, 0.2 * i as f32).normalize(), time * 0.2);
+            nuclei.push(BodyInstance {
+                position: pos.extend(comet.size).to_array(),
+                rotation: spin.to_array(),
+            });
+            tails.push(CometInstance {
+                nucleus: pos.extend(comet.size).to_array(),
+                anti_sun: (rel / r).extend(comet.tail_length * activity).to_array(),
+                motion: vel.normalize_or_zero().extend(activity).to_array(),
+            });
+        }
+        queue.write_buffer(&self.nucleus_buf, 0, bytemuck::cast_slice(&nuclei));
+        queue.write_buffer(&self.tail_buf, 0, bytemuck::cast_slice(&tails));
+    }
+
+    /// Dibuja los núcleos con el pipeline de asteroides ya fijado
+    pub fn draw_nuclei<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if self.comets.is_empty() {
+            return;
+        }
+        pass.set_vertex_buffer(0, self.vb.slice(..));
+        pass.set_vertex_buffer(1, self.nucleus_buf.slice(..));
+        pass.set_index_buffer(self.ib.slice(..), wgpu::IndexFormat::Uint32);
+        pass.draw_indexed(0..self.icount, 0, 0..self.comets.len() as u32);
+    }
+
+    /// Dibuja la coma y las colas con el pipeline de colas ya fijado
+    pub fn draw_tails<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if self.comets.is_empty() {
+            return;
+        }
+        pass.set_vertex_buffer(0, self.tail_buf.slice(..));
+        pass.draw(0..TAIL_VERTICES, 0..self.comets.len() as u32);
+    }
+}
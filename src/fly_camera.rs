@@ -0,0 +1,123 @@
+use glam::{Mat4, Quat, Vec2, Vec3};
+use serde::Deserialize;
+use crate::camera::CollisionSphere;
+use crate::input::controls_section;
+
+/// Ajustes de la cámara de vuelo libre, sección [fly] del archivo de controles
+#[derive(Clone, Copy, Deserialize)]
+pub struct FlySettings {
+    pub min_speed: f32,
+    pub max_speed: f32,
+    pub speed_per_distance: f32, // velocidad por unidad de distancia a la superficie más cercana
+    pub boost: f32,              // multiplicador con la tecla de turbo
+}
+
+impl FlySettings {
+    /// Sección [fly] de `path`, o la de los controles por defecto
+    pub fn load(path: &str) -> Self {
+        controls_section(path, "fly", Self::check)
+    }
+
+    // Escritas así para que un NaN tampoco pase
+    fn check(&self) -> Result<(), String> {
+        if !(self.min_speed > 0.0 && self.min_speed <= self.max_speed) {
+            return Err("hace falta 0 < min_speed <= max_speed".to_string());
+        }
+        if !(self.speed_per_distance >= 0.0 && self.boost > 0.0) {
+            return Err("speed_per_distance no puede ser negativa y boost debe ser positivo".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Cámara de depuración con seis grados de libertad: se mueve en los tres
+/// ejes locales y gira en cualquier dirección, sin colisiones. La velocidad
+/// crece con la distancia al cuerpo más cercano, así que sirve tanto para
+/// rozar una superficie como para cruzar el sistema.
+pub struct FlyCamera {
+    pub position: Vec3,
+    pub orientation: Quat, // mira hacia -Z local con +Y arriba
+    pub settings: FlySettings,
+    speed_scale: f32, // ajustado con la rueda del ratón
+}
+
+impl FlyCamera {
+    pub fn new(settings: FlySettings) -> Self {
+        Self {
+            position: Vec3::ZERO,
+            orientation: Quat::IDENTITY,
+            settings,
+            speed_scale: 1.0,
+        }
+    }
+
+    /// Coloca la cámara en `eye` mirando hacia `target`
+    pub fn look_at(&mut self, eye: Vec3, target: Vec3) {
+        self.position = eye;
+        let view = Mat4::look_at_rh(eye, target, Vec3::Y);
+        self.orientation = Quat::from_mat4(&view.inverse()).normalize();
+    }
+
+    /// `movement` en ejes locales (derecha, arriba, adelante), `roll` positivo
+    /// hacia la derecha
+    pub fn update(&mut self, dt: f32, mouse_delta: Vec2, movement: Vec3, roll: f32, boost: bool, bodies: &[CollisionSphere]) {
+        let sensitivity = 0.002;
+        let roll_speed = 1.5;
+
+        // Los giros son sobre los ejes de la propia cámara
+        self.orientation = (self.orientation
+            * Quat::from_rotation_y(-mouse_delta.x * sensitivity)
+            * Quat::from_rotation_x(-mouse_delta.y * sensitivity)
+            * Quat::from_rotation_z(-roll * roll_speed * dt))
+            .normalize();
+
+        if movement == Vec3::ZERO {
+            return;
+        }
+        let s = &self.settings;
+        let clearance = bodies
+            .iter()
+            .map(|b| self.position.distance(b.center) - b.radius)
+            .fold(f32::INFINITY, f32::min)
+            .max(0.0);
+        let mut speed = (clearance * s.speed_per_distance * self.speed_scale).clamp(s.min_speed, s.max_speed);
+        if boost {
+            speed *= s.boost;
+        }
+
+        let local = Vec3::new(movement.x, movement.y, -movement.z).normalize();
+        self.position += self.orientation * local * speed * dt;
+    }
+
+    /// La rueda del ratón escala la velocidad
+    pub fn scale_speed(&mut self, scroll: f32) {
+        self.speed_scale = (self.speed_scale * 2.0_f32.powf(scroll)).clamp(0.1, 10.0);
+        println!("Velocidad de vuelo: x{:.2}", self.speed_scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_settings_fall_back_to_defaults() {
+        let defaults = FlySettings::load("");
+        let path = std::env::temp_dir().join("solarsoft_fly_test.toml");
+        for fly in [
+            "min_speed = 50.0\nmax_speed = 10.0\nspeed_per_distance = 0.5\nboost = 4.0",
+            "min_speed = 0.0\nmax_speed = 10.0\nspeed_per_distance = 0.5\nboost = 4.0",
+            "min_speed = nan\nmax_speed = 10.0\nspeed_per_distance = 0.5\nboost = 4.0",
+            "min_speed = 1.0\nmax_speed = 10.0\nspeed_per_distance = 0.5\nboost = -1.0",
+        ] {
+            std::fs::write(&path, format!("[fly]\n{}\n", fly)).unwrap();
+            let settings = FlySettings::load(path.to_str().unwrap());
+            assert_eq!(settings.min_speed, defaults.min_speed, "{}", fly);
+            assert_eq!(settings.boost, defaults.boost, "{}", fly);
+        }
+
+        std::fs::write(&path, "[fly]\nmin_speed = 1.0\nmax_speed = 1.0\nspeed_per_distance = 0.0\nboost = 2.0\n").unwrap();
+        assert_eq!(FlySettings::load(path.to_str().unwrap()).max_speed, 1.0);
+        std::fs::remove_file(&path).ok();
+    }
+}
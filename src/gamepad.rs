@@ -0,0 +1,201 @@
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use serde::Deserialize;
+use winit::event::ElementState;
+use crate::input::{controls_section, AnalogControls, InputMap, PadButton};
+
+/// Ejes que se pueden asignar en la sección [gamepad] del archivo de controles
+#[derive(Clone, Copy, Deserialize)]
+pub enum PadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub struct AxisSettings {
+    pub axis: PadAxis,
+    #[serde(default = "default_sensitivity")]
+    pub sensitivity: f32,
+    #[serde(default)]
+    pub invert: bool,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub struct GamepadSettings {
+    pub deadzone: f32,
+    pub curve: f32,
+    pub yaw: AxisSettings,
+    pub pitch: AxisSettings,
+    pub roll: AxisSettings,
+    pub thrust: AxisSettings,
+}
+
+fn default_sensitivity() -> f32 {
+    1.0
+}
+
+impl GamepadSettings {
+    /// Sección [gamepad] de `path`, o la de los controles por defecto
+    pub fn load(path: &str) -> Self {
+        controls_section(path, "gamepad", Self::check)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if !((0.0..1.0).contains(&self.deadzone) && self.curve > 0.0) {
+            return Err("hace falta 0 <= deadzone < 1 y curve > 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Zona muerta, curva de respuesta, sensibilidad e inversión. En los
+    /// sticks `magnitude` es la inclinación del stick entero: la zona muerta
+    /// es un círculo y no un cuadrado, así que en diagonal no se pierde un
+    /// eje al cruzarla. En los gatillos es |raw|.
+    fn shape(&self, axis: &AxisSettings, raw: f32, magnitude: f32) -> f32 {
+        if magnitude <= self.deadzone {
+            return 0.0;
+        }
+        // Se reescala para que el eje no salte al salir de la zona muerta
+        let t = ((magnitude - self.deadzone) / (1.0 - self.deadzone).max(1e-3)).min(1.0);
+        let value = raw / magnitude * t.powf(self.curve.max(0.1)) * axis.sensitivity;
+        if axis.invert { -value } else { value }
+    }
+}
+
+/// Mandos vía gilrs. Los botones pasan por el mismo InputMap que el teclado
+/// (como Pad.*) y los ejes se devuelven ya procesados para la nave.
+pub struct GamepadInput {
+    gilrs: Gilrs,
+    settings: GamepadSettings,
+    active: Option<GamepadId>, // el último mando que se usó
+}
+
+impl GamepadInput {
+    /// None si el sistema no da acceso a los mandos
+    pub fn new(controls_path: &str) -> Option<Self> {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => gilrs,
+            Err(e) => {
+                println!("Mandos no disponibles ({})", e);
+                return None;
+            }
+        };
+        let active = gilrs.gamepads().next().map(|(id, pad)| {
+            println!("Mando conectado: {}", pad.name());
+            id
+        });
+        Some(Self {
+            gilrs,
+            settings: GamepadSettings::load(controls_path),
+            active,
+        })
+    }
+
+    /// Pasa los botones a `input` y devuelve los ejes del mando activo
+    pub fn poll(&mut self, input: &mut InputMap) -> AnalogControls {
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::Connected => {
+                    println!("Mando conectado: {}", self.gilrs.gamepad(event.id).name());
+                    self.active.get_or_insert(event.id);
+                }
+                EventType::Disconnected => {
+                    println!("Mando desconectado: {}", self.gilrs.gamepad(event.id).name());
+                    if self.active == Some(event.id) {
+                        self.active = self.gilrs.gamepads().map(|(id, _)| id).find(|&id| id != event.id);
+                        input.release_pad();
+                    }
+                }
+                EventType::ButtonPressed(button, _) => {
+                    self.active = Some(event.id);
+                    if let Some(button) = pad_button(button) {
+                        input.process_pad(button, ElementState::Pressed);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(button) = pad_button(button) {
+                        input.process_pad(button, ElementState::Released);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let Some(pad) = self.active.and_then(|id| self.gilrs.connected_gamepad(id)) else {
+            return AnalogControls::default();
+        };
+        let left = pad.value(Axis::LeftStickX).hypot(pad.value(Axis::LeftStickY));
+        let right = pad.value(Axis::RightStickX).hypot(pad.value(Axis::RightStickY));
+        // Los gatillos analógicos llegan como botones con valor 0..1
+        let trigger = |button: Button| {
+            let value = pad.button_data(button).map_or(0.0, |b| b.value());
+            (value, value.abs())
+        };
+        let read = |axis: &AxisSettings| {
+            let (raw, magnitude) = match axis.axis {
+                PadAxis::LeftStickX => (pad.value(Axis::LeftStickX), left),
+                PadAxis::LeftStickY => (pad.value(Axis::LeftStickY), left),
+                PadAxis::RightStickX => (pad.value(Axis::RightStickX), right),
+                PadAxis::RightStickY => (pad.value(Axis::RightStickY), right),
+                PadAxis::LeftTrigger => trigger(Button::LeftTrigger2),
+                PadAxis::RightTrigger => trigger(Button::RightTrigger2),
+            };
+            self.settings.shape(axis, raw, magnitude)
+        };
+        AnalogControls {
+            yaw: read(&self.settings.yaw),
+            pitch: read(&self.settings.pitch),
+            roll: read(&self.settings.roll),
+            thrust: read(&self.settings.thrust),
+        }
+    }
+}
+
+fn pad_button(button: Button) -> Option<PadButton> {
+    Some(match button {
+        Button::South => PadButton::South,
+        Button::East => PadButton::East,
+        Button::North => PadButton::North,
+        Button::West => PadButton::West,
+        Button::LeftTrigger => PadButton::LeftBumper,
+        Button::RightTrigger => PadButton::RightBumper,
+        Button::LeftTrigger2 => PadButton::LeftTrigger,
+        Button::RightTrigger2 => PadButton::RightTrigger,
+        Button::Select => PadButton::Select,
+        Button::Start => PadButton::Start,
+        Button::Mode => PadButton::Mode,
+        Button::LeftThumb => PadButton::LeftThumb,
+        Button::RightThumb => PadButton::RightThumb,
+        Button::DPadUp => PadButton::DPadUp,
+        Button::DPadDown => PadButton::DPadDown,
+        Button::DPadLeft => PadButton::DPadLeft,
+        Button::DPadRight => PadButton::DPadRight,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radial_deadzone() {
+        let settings = GamepadSettings::load("");
+        let axis = AxisSettings { axis: PadAxis::LeftStickX, sensitivity: 1.0, invert: false };
+        let stick = |x: f32, y: f32| {
+            let magnitude = x.hypot(y);
+            (settings.shape(&axis, x, magnitude), settings.shape(&axis, y, magnitude))
+        };
+
+        assert_eq!(stick(0.1, 0.0), (0.0, 0.0));
+        // En diagonal cuenta la inclinación total, no la de cada eje
+        let (x, y) = stick(0.12, 0.12);
+        assert!(x > 0.0 && (x - y).abs() < 1e-6);
+        // La dirección se conserva y a fondo se llega a 1
+        let (x, y) = stick(-0.6, 0.8);
+        assert!((x + 0.6).abs() < 1e-5 && (y - 0.8).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::input::Action;
+
+/// Formato del render target de la escena antes del tone mapping
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Niveles de la cadena de bloom (el primero a media resolución)
+const BLOOM_MIPS: u32 = 5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ToneMapping {
+    Aces,
+    Reinhard,
+    None, // solo exposición y recorte, para comparar
+}
+
+impl ToneMapping {
+    fn next(self) -> Self {
+        match self {
+            ToneMapping::Aces => ToneMapping::Reinhard,
+            ToneMapping::Reinhard => ToneMapping::None,
+            ToneMapping::None => ToneMapping::Aces,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct HdrUniforms {
+    exposure: f32,
+    bloom_intensity: f32,
+    threshold: f32,
+    knee: f32,
+    tonemap_op: u32,
+    encode_srgb: u32, // 1 si la salida no es un formato *Srgb
+    _pad: [u32; 2],
+}
+
+/// Render target HDR, cadena de bloom y pase final de tone mapping
+pub struct Hdr {
+    pub exposure: f32,
+    pub tone_mapping: ToneMapping,
+    pub bloom_enabled: bool,
+    pub bloom_intensity: f32,
+    pub bloom_threshold: f32,
+    encode_srgb: bool,
+
+    pub view: wgpu::TextureView, // destino (o resolve) del pase principal
+    bloom_views: Vec<wgpu::TextureView>,
+    uniform_buf: wgpu::Buffer,
+
+    prefilter_pipeline: wgpu::RenderPipeline,
+    downsample_pipeline: wgpu::RenderPipeline,
+    upsample_pipeline: wgpu::RenderPipeline,
+    tonemap_pipeline: wgpu::RenderPipeline,
+
+    prefilter_bg: wgpu::BindGroup,
+    downsample_bgs: Vec<wgpu::BindGroup>, // [i] lee el nivel i
+    upsample_bgs: Vec<wgpu::BindGroup>,   // [i] lee el nivel i + 1
+    tonemap_bg: wgpu::BindGroup,
+}
+
+impl Hdr {
+    pub fn new(device: &wgpu::Device, output_format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("HDR Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Cadena de bloom: una textura con mips, empezando a media resolución
+        let bloom_size = wgpu::Extent3d {
+            width: (width / 2).max(1),
+            height: (height / 2).max(1),
+            depth_or_array_layers: 1,
+        };
+        let mips = BLOOM_MIPS.min(bloom_size.max_mips(wgpu::TextureDimension::D2));
+        let bloom_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Bloom Texture"),
+            size: bloom_size,
+            mip_level_count: mips,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let bloom_views: Vec<wgpu::TextureView> = (0..mips)
+            .map(|mip| {
+                bloom_texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Bloom Mip"),
+                    base_mip_level: mip,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let uniform_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("HDR Uniforms"),
+            size: std::mem::size_of::<HdrUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("HDR Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        // 0: textura de entrada, 1: sampler, 2: uniforms, 3: bloom (solo tone mapping)
+        let bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("HDR Layout"),
+            entries: &[
+                texture_entry(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(3),
+            ],
+        });
+
+        let make_bg = |input: &wgpu::TextureView, bloom: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("HDR BG"),
+                layout: &bg_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(input),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: uniform_buf.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(bloom),
+                    },
+                ],
+            })
+        };
+
+        let prefilter_bg = make_bg(&view, &view);
+        let downsample_bgs = bloom_views.iter().map(|v| make_bg(v, v)).collect();
+        let upsample_bgs = bloom_views[1..].iter().map(|v| make_bg(v, v)).collect();
+        let tonemap_bg = make_bg(&view, &bloom_views[0]);
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shader_hdr.wgsl"));
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("HDR Pipeline Layout"),
+            bind_group_layouts: &[&bg_layout],
+            push_constant_ranges: &[],
+        });
+
+        let make_pipeline = |label, entry_point, format, blend| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_fullscreen",
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::OVER,
+        };
+
+        let prefilter_pipeline = make_pipeline("Bloom Prefilter", "fs_prefilter", HDR_FORMAT, None);
+        let downsample_pipeline = make_pipeline("Bloom Downsample", "fs_downsample", HDR_FORMAT, None);
+        let upsample_pipeline = make_pipeline("Bloom Upsample", "fs_upsample", HDR_FORMAT, Some(additive));
+        let tonemap_pipeline = make_pipeline("Tonemap", "fs_tonemap", output_format, None);
+
+        Self {
+            exposure: 1.0,
+            tone_mapping: ToneMapping::Aces,
+            bloom_enabled: true,
+            bloom_intensity: 0.6,
+            bloom_threshold: 1.0,
+            encode_srgb: !output_format.is_srgb(),
+            view,
+            bloom_views,
+            uniform_buf,
+            prefilter_pipeline,
+            downsample_pipeline,
+            upsample_pipeline,
+            tonemap_pipeline,
+            prefilter_bg,
+            downsample_bgs,
+            upsample_bgs,
+            tonemap_bg,
+        }
+    }
+
+    pub fn process_action(&mut self, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+
+        match action {
+            Action::CycleToneMapping => {
+                self.tone_mapping = self.tone_mapping.next();
+                println!("Tone mapping: {:?}", self.tone_mapping);
+            }
+            Action::ToggleBloom => {
+                self.bloom_enabled = !self.bloom_enabled;
+                println!("Bloom: {}", if self.bloom_enabled { "ACTIVADO" } else { "DESACTIVADO" });
+            }
+            Action::ExposureUp => {
+                self.exposure = (self.exposure * 1.25).min(16.0);
+                println!("Exposición: {:.2}", self.exposure);
+            }
+            Action::ExposureDown => {
+                self.exposure = (self.exposure / 1.25).max(1.0 / 16.0);
+                println!("Exposición: {:.2}", self.exposure);
+            }
+            _ => {}
+        }
+    }
+
+    /// Bloom + tone mapping de la imagen HDR hacia `output`
+    pub fn resolve(&self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, output: &wgpu::TextureView) {
+        let uniforms = HdrUniforms {
+            exposure: self.exposure,
+            bloom_intensity: if self.bloom_enabled { self.bloom_intensity } else { 0.0 },
+            threshold: self.bloom_threshold,
+            knee: 0.5,
+            tonemap_op: match self.tone_mapping {
+                ToneMapping::Aces => 0,
+                ToneMapping::Reinhard => 1,
+                ToneMapping::None => 2,
+            },
+            encode_srgb: self.encode_srgb as u32,
+            _pad: [0; 2],
+        };
+        queue.write_buffer(&self.uniform_buf, 0, bytemuck::bytes_of(&uniforms));
+
+        if self.bloom_enabled {
+            // Brillos por encima del umbral -> nivel 0
+            Self::fullscreen_pass(encoder, "Bloom Prefilter", &self.bloom_views[0], true, &self.prefilter_pipeline, &self.prefilter_bg);
+
+            // Bajada: nivel i -> nivel i + 1
+            for i in 0..self.bloom_views.len() - 1 {
+                Self::fullscreen_pass(encoder, "Bloom Downsample", &self.bloom_views[i + 1], true, &self.downsample_pipeline, &self.downsample_bgs[i]);
+            }
+
+            // Subida aditiva: nivel i + 1 se suma sobre el nivel i
+            for i in (0..self.bloom_views.len() - 1).rev() {
+                Self::fullscreen_pass(encoder, "Bloom Upsample", &self.bloom_views[i], false, &self.upsample_pipeline, &self.upsample_bgs[i]);
+            }
+        }
+
+        Self::fullscreen_pass(encoder, "Tonemap", output, true, &self.tonemap_pipeline, &self.tonemap_bg);
+    }
+
+    fn fullscreen_pass(
+        encoder: &mut wgpu::CommandEncoder,
+        label: &str,
+        target: &wgpu::TextureView,
+        clear: bool,
+        pipeline: &wgpu::RenderPipeline,
+        bind_group: &wgpu::BindGroup,
+    ) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: if clear {
+                        wgpu::LoadOp::Clear(wgpu::Color::BLACK)
+                    } else {
+                        wgpu::LoadOp::Load
+                    },
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.draw(0..3, 0..1); // triángulo que cubre toda la pantalla
+    }
+}
@@ -0,0 +1,171 @@
+use glam::Vec2;
+use crate::renderer::Renderer;
+use crate::input::Action;
+use crate::{arg_value, load_camera, load_world, msaa_sample_count, request_device};
+
+/// Los frames se leen de la GPU tal cual se escriben en el PNG
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Renderiza sin ventana una secuencia de PNG con paso de tiempo fijo, para
+/// máquinas sin pantalla. Opciones, además de --scene, --camera-path y --msaa:
+///
+///   --size ANCHOxALTO   resolución (por defecto 1280x720)
+///   --fps N             frames por segundo simulado (por defecto 30)
+///   --frames N          cuántos frames (por defecto, lo que dure el recorrido)
+///   --output DIR        carpeta de salida (por defecto frames/)
+///   --software          exige un adaptador por CPU (lavapipe, llvmpipe, WARP)
+///
+/// Sin teclado la cámara solo se mueve si hay un recorrido, que empieza a
+/// reproducirse en el primer frame. Si algo falla devuelve el mensaje.
+pub async fn run() -> Result<(), String> {
+    let (width, height) = match arg_value("--size").map(|s| parse_size(&s)) {
+        None => (1280, 720),
+        Some(Some(size)) => size,
+        Some(None) => return Err("--size debe ser ANCHOxALTO, p. ej. 1920x1080".to_string()),
+    };
+    let fps: f32 = match arg_value("--fps").map(|v| v.parse()) {
+        None => 30.0,
+        Some(Ok(fps)) if fps > 0.0 => fps,
+        Some(_) => return Err("--fps debe ser un número positivo".to_string()),
+    };
+    let output = arg_value("--output").unwrap_or_else(|| "frames".to_string());
+
+    // WGPU_BACKEND=vulkan|gl|... elige el backend, como en los ejemplos de wgpu
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::util::backend_bits_from_env().unwrap_or_default(),
+        ..Default::default()
+    });
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            force_fallback_adapter: std::env::args().any(|a| a == "--software"),
+            ..Default::default()
+        })
+        .await;
+    let adapter = adapter.ok_or("No hay ningún adaptador gráfico disponible")?;
+    let info = adapter.get_info();
+    println!("Adaptador: {} ({:?})", info.name, info.backend);
+    let (device, queue) = request_device(&adapter).await;
+    let max_size = device.limits().max_texture_dimension_2d;
+    if width > max_size || height > max_size {
+        return Err(format!("--size {}x{} supera el máximo del adaptador ({} por lado)", width, height, max_size));
+    }
+
+    let mut renderer = Renderer::new(&device, FORMAT, width, height, msaa_sample_count(&adapter)).await;
+    let controls_path = arg_value("--controls").unwrap_or_else(|| "src/config/controles.toml".to_string());
+    let mut cam = load_camera(&controls_path);
+    let mut world = load_world(&device, &queue, &renderer);
+
+    if cam.path.is_empty() {
+        println!("Sin recorrido de cámara en {}: vista orbital fija", cam.path.path());
+    } else {
+        cam.process_action(Action::PlayPath, true);
+    }
+    let frames = match arg_value("--frames").map(|v| v.parse::<u32>()) {
+        None => (cam.path.duration() * fps).round() as u32 + 1,
+        Some(Ok(n)) if n > 0 => n,
+        Some(_) => return Err("--frames debe ser un entero positivo".to_string()),
+    };
+
+    std::fs::create_dir_all(&output).map_err(|e| format!("No se pudo crear {} ({})", output, e))?;
+
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Headless Target"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+    // Las filas de una copia textura -> buffer van alineadas a 256 bytes
+    let row_bytes = width * 4;
+    let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Headless Readback"),
+        size: (padded_row_bytes * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let start = std::time::Instant::now();
+    for i in 0..frames {
+        // El frame i es el instante i / fps, así que el primero no avanza
+        let dt = if i == 0 { 0.0 } else { 1.0 / fps };
+        world.update(&device, &queue, &mut cam, dt, Vec2::ZERO, renderer.shadows.enabled);
+        world.render(&device, &queue, &mut renderer, &cam, &view, (width, height));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            target.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let mapped = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let result = mapped.clone();
+        slice.map_async(wgpu::MapMode::Read, move |r| *result.lock().unwrap() = Some(r));
+        device.poll(wgpu::Maintain::Wait);
+        match mapped.lock().unwrap().take() {
+            Some(Ok(())) => {}
+            Some(Err(e)) => return Err(format!("No se pudo leer el frame {} ({})", i, e)),
+            None => return Err(format!("No se pudo leer el frame {}", i)),
+        }
+        // El PNG va sin alfa: la imagen es opaca
+        let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+        for row in slice.get_mapped_range().chunks_exact(padded_row_bytes as usize) {
+            for pixel in row[..row_bytes as usize].chunks_exact(4) {
+                rgb.extend_from_slice(&pixel[..3]);
+            }
+        }
+        readback.unmap();
+
+        let path = format!("{}/frame_{:05}.png", output, i);
+        write_png(&path, width, height, &rgb).map_err(|e| format!("No se pudo escribir {} ({})", path, e))?;
+        println!("Frame {}/{}: {}", i + 1, frames, path);
+    }
+    println!("{} frames a {}x{} en {:.1} s", frames, width, height, start.elapsed().as_secs_f32());
+    Ok(())
+}
+
+/// "1920x1080"
+fn parse_size(text: &str) -> Option<(u32, u32)> {
+    let (w, h) = text.split_once('x')?;
+    let (w, h) = (w.trim().parse().ok()?, h.trim().parse().ok()?);
+    (w > 0 && h > 0).then_some((w, h))
+}
+
+fn write_png(path: &str, width: u32, height: u32, rgb: &[u8]) -> Result<(), png::EncodingError> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("1920x1080"), Some((1920, 1080)));
+        assert_eq!(parse_size(" 64 x 36 "), Some((64, 36)));
+        assert_eq!(parse_size("0x720"), None);
+        assert_eq!(parse_size("1280"), None);
+        assert_eq!(parse_size("1280x"), None);
+        assert_eq!(parse_size("-1x10"), None);
+        assert_eq!(parse_size("1280*720"), None);
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use winit::event::{ElementState, KeyEvent, MouseButton, WindowEvent};
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
+
+/// Controles por defecto, los mismos del archivo de configuración
+pub const DEFAULT_CONTROLS: &str = include_str!("config/controles.toml");
+
+/// Sección `[key]` del archivo de controles `path` (los ajustes de vuelo
+/// libre, los ejes del mando...), que además debe pasar `check`. Si el
+/// archivo no se puede leer se usa la de los controles por defecto, sin
+/// avisar: ya lo hace `InputMap::load`.
+pub fn controls_section<T: DeserializeOwned>(path: &str, key: &str, check: fn(&T) -> Result<(), String>) -> T {
+    let parse = |text: &str| -> Result<T, String> {
+        let mut table: toml::Table = toml::from_str(text).map_err(|e| e.to_string())?;
+        let section = table.remove(key).ok_or(format!("falta la sección [{}]", key))?;
+        let value: T = section.try_into().map_err(|e: toml::de::Error| e.to_string())?;
+        check(&value).map(|_| value)
+    };
+    let default = || parse(DEFAULT_CONTROLS).expect("controles por defecto no válidos");
+    match std::fs::read_to_string(path) {
+        Ok(text) => parse(&text).unwrap_or_else(|e| {
+            println!("Sección [{}] no válida en {} ({}), usando la de por defecto", key, path, e);
+            default()
+        }),
+        Err(_) => default(),
+    }
+}
+
+/// Lo que el usuario quiere hacer, independiente de la tecla o botón que lo
+/// dispare. Los nombres en el archivo de controles van en snake_case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Boost,
+    ToggleView,
+    ToggleFly,
+    TurnLeft,
+    TurnRight,
+    TurnUp,
+    TurnDown,
+    RollLeft,
+    RollRight,
+    Thrust,
+    CycleToneMapping,
+    ToggleBloom,
+    ExposureUp,
+    ExposureDown,
+    TogglePost,
+    #[serde(rename = "post_effect_1")]
+    PostEffect1,
+    #[serde(rename = "post_effect_2")]
+    PostEffect2,
+    #[serde(rename = "post_effect_3")]
+    PostEffect3,
+    #[serde(rename = "post_effect_4")]
+    PostEffect4,
+    PostNextParam,
+    PostParamUp,
+    PostParamDown,
+    ToggleShadows,
+    TogglePropagation,
+    #[serde(rename = "toggle_nbody")]
+    ToggleNBody,
+    GrabCursor,
+    ReleaseCursor,
+    FovIn,
+    FovOut,
+    PlayPath,
+    TogglePathEditor,
+    AddKeyframe,
+    RemoveKeyframe,
+    // En el archivo van como save_bookmark_N y recall_bookmark_N (N = 1..9)
+    #[serde(skip)]
+    SaveBookmark(u8),
+    #[serde(skip)]
+    RecallBookmark(u8),
+}
+
+impl Action {
+    fn from_name(name: &str) -> Result<Self, String> {
+        let slot = |n: &str| match n.parse::<u8>() {
+            Ok(n @ 1..=9) => Ok(n),
+            _ => Err(format!("marcador \"{}\" fuera de 1..9", n)),
+        };
+        if let Some(n) = name.strip_prefix("save_bookmark_") {
+            return slot(n).map(Action::SaveBookmark);
+        }
+        if let Some(n) = name.strip_prefix("recall_bookmark_") {
+            return slot(n).map(Action::RecallBookmark);
+        }
+        Action::deserialize(name.into_deserializer())
+            .map_err(|_: serde::de::value::Error| format!("acción desconocida \"{}\"", name))
+    }
+}
+
+/// Botones de mando, con los nombres de gilrs (ver gamepad.rs). Se aceptan
+/// en el archivo aunque el programa se compile sin soporte de mando.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Ejes analógicos del mando, ya con zona muerta, curva y sensibilidad.
+/// Positivos: girar a la derecha, subir el morro, alabear a la derecha.
+#[derive(Clone, Copy, Default)]
+pub struct AnalogControls {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub roll: f32,
+    pub thrust: f32, // 0..1
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Trigger {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Pad(PadButton),
+}
+
+/// Combinación de modificadores más tecla o botón
+#[derive(Clone, Copy)]
+struct Binding {
+    trigger: Trigger,
+    modifiers: ModifiersState,
+}
+
+impl Binding {
+    /// "Ctrl+Shift+KeyS", "Mouse.Left", "Pad.South", "F1"...
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let name = parts.pop().filter(|n| !n.is_empty()).ok_or(format!("combinación vacía en \"{}\"", text))?;
+
+        let mut modifiers = ModifiersState::empty();
+        for m in parts {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ModifiersState::CONTROL,
+                "shift" => ModifiersState::SHIFT,
+                "alt" => ModifiersState::ALT,
+                "super" | "cmd" | "meta" => ModifiersState::SUPER,
+                _ => return Err(format!("modificador desconocido \"{}\" en \"{}\"", m, text)),
+            };
+        }
+
+        // Los nombres son los de las variantes de winit
+        let trigger = if let Some(button) = name.strip_prefix("Mouse.") {
+            MouseButton::deserialize(button.into_deserializer())
+                .map(Trigger::Mouse)
+                .map_err(|_: serde::de::value::Error| format!("botón desconocido \"{}\"", button))?
+        } else if let Some(button) = name.strip_prefix("Pad.") {
+            PadButton::deserialize(button.into_deserializer())
+                .map(Trigger::Pad)
+                .map_err(|_: serde::de::value::Error| format!("botón de mando desconocido \"{}\"", button))?
+        } else {
+            KeyCode::deserialize(name.into_deserializer())
+                .map(Trigger::Key)
+                .map_err(|_: serde::de::value::Error| format!("tecla desconocida \"{}\"", name))?
+        };
+        Ok(Self { trigger, modifiers })
+    }
+}
+
+/// Traduce los eventos de teclado, ratón y mando en acciones, que se
+/// acumulan hasta que el frame las recoge con `take_actions`. Cada pulsación
+/// recuerda qué acciones activó, así que al soltar se liberan las mismas
+/// aunque entretanto cambien los modificadores.
+pub struct InputMap {
+    bindings: Vec<(Binding, Action)>,
+    modifiers: ModifiersState,
+    held: HashMap<Trigger, Vec<Action>>,
+    pending: Vec<(Action, bool)>,
+}
+
+impl InputMap {
+    /// Lee los controles de `path`; si no se puede, usa los de por defecto
+    pub fn load(path: &str) -> Self {
+        let bindings = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Self::parse(&text));
+        match bindings {
+            Ok(bindings) => Self::new(bindings),
+            Err(e) => {
+                println!("No se pudo cargar {} ({}), usando los controles por defecto", path, e);
+                Self::new(Self::parse(DEFAULT_CONTROLS).expect("controles por defecto no válidos"))
+            }
+        }
+    }
+
+    fn new(bindings: Vec<(Binding, Action)>) -> Self {
+        Self {
+            bindings,
+            modifiers: ModifiersState::empty(),
+            held: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Vec<(Binding, Action)>, String> {
+        let mut table: toml::Table = toml::from_str(text).map_err(|e| e.to_string())?;
+        // Los ejes del mando y la cámara de vuelo van aparte (ver
+        // gamepad.rs y fly_camera.rs)
+        table.remove("gamepad");
+        table.remove("fly");
+        let table: HashMap<String, Vec<String>> = table.try_into().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut bindings = Vec::new();
+        for (name, combos) in table {
+            let action = Action::from_name(&name)?;
+            for combo in combos {
+                bindings.push((Binding::parse(&combo)?, action));
+            }
+        }
+        Ok(bindings)
+    }
+
+    pub fn process_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::ModifiersChanged(m) => self.modifiers = m.state(),
+            // La repetición del sistema no vuelve a disparar las acciones
+            WindowEvent::KeyboardInput {
+                event: KeyEvent { physical_key: PhysicalKey::Code(code), state, repeat: false, .. },
+                ..
+            } => self.trigger(Trigger::Key(*code), *state),
+            WindowEvent::MouseInput { state, button, .. } => self.trigger(Trigger::Mouse(*button), *state),
+            WindowEvent::Focused(false) => {
+                // Sin foco no llegan las liberaciones: se sueltan todas
+                self.modifiers = ModifiersState::empty();
+                let released = self.held.drain().flat_map(|(_, actions)| actions);
+                self.pending.extend(released.map(|a| (a, false)));
+            }
+            _ => {}
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    pub fn process_pad(&mut self, button: PadButton, state: ElementState) {
+        self.trigger(Trigger::Pad(button), state);
+    }
+
+    /// Suelta lo que estuviera pulsado en el mando, p. ej. al desconectarlo
+    #[cfg(feature = "gamepad")]
+    pub fn release_pad(&mut self) {
+        let pads: Vec<Trigger> = self.held.keys().filter(|t| matches!(t, Trigger::Pad(_))).copied().collect();
+        for pad in pads {
+            self.trigger(pad, ElementState::Released);
+        }
+    }
+
+    /// Acciones que se activaron (true) o soltaron (false) desde la última llamada
+    pub fn take_actions(&mut self) -> Vec<(Action, bool)> {
+        std::mem::take(&mut self.pending)
+    }
+
+    fn trigger(&mut self, trigger: Trigger, state: ElementState) {
+        if state == ElementState::Released {
+            let released = self.held.remove(&trigger).unwrap_or_default();
+            self.pending.extend(released.into_iter().map(|a| (a, false)));
+            return;
+        }
+        if self.held.contains_key(&trigger) {
+            return;
+        }
+
+        // Entre las combinaciones que encajan gana la de más modificadores:
+        // Ctrl+S no dispara también lo que tenga S sola
+        let matching = self
+            .bindings
+            .iter()
+            .filter(|(b, _)| b.trigger == trigger && self.modifiers.contains(b.modifiers));
+        let best = matching.clone().map(|(b, _)| b.modifiers.bits().count_ones()).max();
+        let actions: Vec<Action> = matching
+            .filter(|(b, _)| Some(b.modifiers.bits().count_ones()) == best)
+            .map(|&(_, a)| a)
+            .collect();
+        if actions.is_empty() {
+            return;
+        }
+
+        self.pending.extend(actions.iter().map(|&a| (a, true)));
+        self.held.insert(trigger, actions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_controls_parse() {
+        let bindings = InputMap::parse(DEFAULT_CONTROLS).unwrap();
+        assert!(bindings.iter().any(|&(_, a)| a == Action::RecallBookmark(9)));
+    }
+
+    #[test]
+    fn binding_parse() {
+        let b = Binding::parse("Ctrl+Shift+KeyS").unwrap();
+        assert!(b.trigger == Trigger::Key(KeyCode::KeyS));
+        assert_eq!(b.modifiers, ModifiersState::CONTROL | ModifiersState::SHIFT);
+
+        let b = Binding::parse(" alt + Mouse.Left ").unwrap();
+        assert!(b.trigger == Trigger::Mouse(MouseButton::Left));
+        assert_eq!(b.modifiers, ModifiersState::ALT);
+
+        assert!(Binding::parse("Pad.South").unwrap().trigger == Trigger::Pad(PadButton::South));
+        assert!(Binding::parse("Hyper+KeyS").is_err());
+        assert!(Binding::parse("KeyÑ").is_err());
+        assert!(Binding::parse("Ctrl+").is_err());
+    }
+
+    #[test]
+    fn action_names() {
+        assert_eq!(Action::from_name("toggle_nbody"), Ok(Action::ToggleNBody));
+        assert_eq!(Action::from_name("post_effect_3"), Ok(Action::PostEffect3));
+        assert_eq!(Action::from_name("save_bookmark_1"), Ok(Action::SaveBookmark(1)));
+        assert_eq!(Action::from_name("recall_bookmark_9"), Ok(Action::RecallBookmark(9)));
+        assert!(Action::from_name("recall_bookmark_0").is_err());
+        assert!(Action::from_name("save_bookmark").is_err());
+        assert!(Action::from_name("fly_to_the_moon").is_err());
+    }
+
+    #[test]
+    fn most_modifiers_wins() {
+        let bindings = ["KeyS", "Ctrl+KeyS", "Ctrl+Shift+KeyS"]
+            .iter()
+            .zip([Action::MoveBackward, Action::SaveBookmark(1), Action::SaveBookmark(2)])
+            .map(|(text, action)| (Binding::parse(text).unwrap(), action))
+            .collect();
+        let mut input = InputMap::new(bindings);
+        let key = Trigger::Key(KeyCode::KeyS);
+
+        input.trigger(key, ElementState::Pressed);
+        assert_eq!(input.take_actions(), [(Action::MoveBackward, true)]);
+        input.trigger(key, ElementState::Released);
+        assert_eq!(input.take_actions(), [(Action::MoveBackward, false)]);
+
+        // Con Ctrl+Alt gana Ctrl+S: Alt no lo impide y Shift falta
+        input.modifiers = ModifiersState::CONTROL | ModifiersState::ALT;
+        input.trigger(key, ElementState::Pressed);
+        assert_eq!(input.take_actions(), [(Action::SaveBookmark(1), true)]);
+
+        // Al soltar se libera lo mismo aunque cambien los modificadores
+        input.modifiers = ModifiersState::empty();
+        input.trigger(key, ElementState::Released);
+        assert_eq!(input.take_actions(), [(Action::SaveBookmark(1), false)]);
+    }
+}
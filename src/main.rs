@@ -1,24 +1,132 @@
 mod renderer;
+mod hdr;
+mod postprocess;
+mod sun;
+mod shadow;
+mod asteroids;
+mod comets;
+mod particles;
+mod propagator;
+mod nbody;
+mod atmosphere;
+mod clouds;
+mod surface;
+mod terrain;
 mod camera;
+mod fly_camera;
+mod bookmarks;
+mod camera_path;
+mod input;
+#[cfg(feature = "gamepad")]
+mod gamepad;
 mod mesh;
+mod spaceship;
 mod orbit;
+mod trail;
 mod scene;
 mod procedural_texture;
+mod world;
+mod headless;
 
 use std::sync::Arc;
 use winit::{event::*, event_loop::EventLoop};
 use pollster::block_on;
-use glam::{Vec2, Vec3};
-use renderer::{Renderer, Globals};
+use glam::Vec2;
+use renderer::Renderer;
 use camera::Camera;
-use scene::Scene;
-use camera::CollisionSphere;
-use wgpu::util::DeviceExt;
+use fly_camera::FlySettings;
+use bookmarks::Bookmarks;
+use camera_path::CameraPath;
+use input::{Action, InputMap};
+use world::World;
 
 fn main() {
-    block_on(run());
+    // Sin ventana se renderiza a una secuencia de PNG (ver headless.rs)
+    if std::env::args().any(|a| a == "--headless") {
+        if let Err(e) = block_on(headless::run()) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    } else {
+        block_on(run());
+    }
 }
 
+/// Valor de una opción `--nombre valor` de la línea de comandos
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+    }
+    None
+}
+
+/// Dispositivo con las features opcionales que aprovecha el renderer
+async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    // Permite conteos de MSAA distintos de 1 y 4 cuando el adaptador los soporta
+    let features = adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                required_features: features,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap()
+}
+
+/// MSAA: --msaa 1|2|4|8 (por defecto 4), limitado a lo que soporte el adaptador
+fn msaa_sample_count(adapter: &wgpu::Adapter) -> u32 {
+    let requested_msaa = arg_value("--msaa").and_then(|v| v.parse().ok()).unwrap_or(4);
+    let sample_count = Renderer::supported_sample_count(adapter, requested_msaa);
+    if sample_count != requested_msaa {
+        println!("MSAA x{} no soportado, usando x{}", requested_msaa, sample_count);
+    }
+    sample_count
+}
+
+/// Cámara con los ajustes de `--controls` y el recorrido de `--camera-path`;
+/// el editor de recorridos guarda en `--path-output`
+fn load_camera(controls_path: &str) -> Camera {
+    let camera_path = CameraPath::load(
+        &arg_value("--camera-path").unwrap_or_else(|| "src/scenes/recorrido.toml".to_string()),
+        &arg_value("--path-output").unwrap_or_else(|| "recorrido.toml".to_string()),
+    );
+    Camera::new(FlySettings::load(controls_path), camera_path)
+}
+
+/// Escena de `--scene` con todo lo que se dibuja de ella
+fn load_world(device: &wgpu::Device, queue: &wgpu::Queue, renderer: &Renderer) -> World {
+    let scene_path = arg_value("--scene").unwrap_or_else(|| "src/scenes/sistema.toml".to_string());
+    World::new(device, queue, renderer, &scene_path)
+}
+
+/// Captura y oculta el cursor para mirar con el ratón, o lo suelta.
+/// Devuelve si el cursor quedó capturado, haya salido bien o no.
+fn grab_cursor(window: &winit::window::Window, grab: bool) -> bool {
+    use winit::window::CursorGrabMode;
+    let result = if grab {
+        // Locked no existe en X11 y Confined no existe en macOS
+        window
+            .set_cursor_grab(CursorGrabMode::Locked)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+    } else {
+        window.set_cursor_grab(CursorGrabMode::None)
+    };
+    if let Err(e) = result {
+        println!("No se pudo {} el cursor ({})", if grab { "capturar" } else { "soltar" }, e);
+        return !grab;
+    }
+    window.set_cursor_visible(!grab);
+    grab
+}
+
+// EventLoop::run y create_window están obsoletos en winit 0.30, pero siguen funcionando
+#[allow(deprecated)]
 async fn run() {
     let event_loop = EventLoop::new().unwrap();
 
@@ -34,7 +142,7 @@ async fn run() {
     let instance = wgpu::Instance::default();
     let surface = instance.create_surface(window.clone()).unwrap();
     let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions::default()).await.unwrap();
-    let (device, queue) = adapter.request_device(&wgpu::DeviceDescriptor::default(), None).await.unwrap();
+    let (device, queue) = request_device(&adapter).await;
 
     let size = window.inner_size();
     let format = surface.get_capabilities(&adapter).formats[0];
@@ -51,13 +159,22 @@ async fn run() {
     };
     surface.configure(&device, &config);
 
-    let renderer = Renderer::new(&device, format, size.width, size.height).await;
-    let mut cam = Camera::new();
-    let mut scene = Scene::load_models(&device);
+    let sample_count = msaa_sample_count(&adapter);
+    let mut renderer = Renderer::new(&device, format, size.width, size.height, sample_count).await;
+    let controls_path = arg_value("--controls").unwrap_or_else(|| "src/config/controles.toml".to_string());
+    let mut cam = load_camera(&controls_path);
+    let mut input = InputMap::load(&controls_path);
+    let mut bookmarks = Bookmarks::load(&arg_value("--bookmarks").unwrap_or_else(|| "marcadores.toml".to_string()));
+    #[cfg(feature = "gamepad")]
+    let mut gamepad = gamepad::GamepadInput::new(&controls_path);
+    let mut world = load_world(&device, &queue, &renderer);
 
-    let mut time = 0.0f32;
     let mut last = std::time::Instant::now();
     let mut mouse_delta = Vec2::ZERO;
+    let mut focused = false;
+    let mut cursor_grabbed = false;
+    // Si se captura al recibir el foco: sí, salvo que se haya soltado a mano
+    let mut grab_on_focus = true;
 
     event_loop
         .run(move |event, elwt| match event {
@@ -66,8 +183,10 @@ async fn run() {
             Event::WindowEvent { event: WindowEvent::CloseRequested, .. } => elwt.exit(),
 
             /* ---------- Mouse movement ---------- */
-            Event::DeviceEvent { event, .. } => match event {
-                DeviceEvent::MouseMotion { delta } => {
+            // Los eventos de dispositivo llegan aunque la ventana no tenga el
+            // foco: sin cursor capturado el ratón no mueve la cámara
+            Event::DeviceEvent { event, .. } if focused => match event {
+                DeviceEvent::MouseMotion { delta } if cursor_grabbed => {
                     mouse_delta += Vec2::new(delta.0 as f32, delta.1 as f32);
                 }
                 DeviceEvent::MouseWheel { delta } => {
@@ -76,17 +195,35 @@ async fn run() {
                         MouseScrollDelta::LineDelta(_, y) => y * zoom_speed,
                         MouseScrollDelta::PixelDelta(p) => (p.y as f32) * zoom_speed,
                     };
-                    cam.radius = (cam.radius - scroll).clamp(3.0, 500.0);
+                    // En vuelo libre la rueda cambia la velocidad en vez del zoom
+                    if cam.fly_view {
+                        cam.fly.scale_speed(scroll);
+                    } else {
+                        cam.radius = (cam.radius - scroll).clamp(3.0, 500.0);
+                    }
                 }
                 _ => {}
             },
 
-            /* ---------- Keyboard movement ---------- */
+            /* ---------- Teclado y botones del ratón, a acciones ---------- */
             Event::WindowEvent {
-                event: WindowEvent::KeyboardInput { event: KeyEvent { logical_key, state, .. }, .. },
+                event: event @ (WindowEvent::KeyboardInput { .. }
+                    | WindowEvent::MouseInput { .. }
+                    | WindowEvent::ModifiersChanged(_)
+                    | WindowEvent::Focused(_)),
                 ..
             } => {
-                cam.process_key(&logical_key, state);
+                if let WindowEvent::Focused(now_focused) = event {
+                    focused = now_focused;
+                    if focused {
+                        if grab_on_focus {
+                            cursor_grabbed = grab_cursor(&window, true);
+                        }
+                    } else if cursor_grabbed {
+                        cursor_grabbed = grab_cursor(&window, false);
+                    }
+                }
+                input.process_event(&event);
             }
 
             /* ---------- Pedimos redibujar continuamente ---------- */
@@ -94,12 +231,20 @@ async fn run() {
                 window.clone().request_redraw();
                 
                 // Actualizar título de ventana para mostrar modo
-                let title = if cam.ship_view {
+                let title = if cam.path_playing() {
+                    "Space Travel - Recorrido (K para parar)"
+                } else if cam.fly_view {
+                    "Space Travel - Vuelo libre (C para salir)"
+                } else if cam.ship_view {
                     "Space Travel - Vista nave (V para cambiar)"
                 } else {
                     "Space Travel - Vista libre (V para cambiar)"
                 };
-                window.set_title(title);
+                if cam.path_editing() {
+                    window.set_title(&format!("{} - Editor de recorrido, {} claves", title, cam.path.len()));
+                } else {
+                    window.set_title(title);
+                }
             }
 
             /* ---------- Render ---------- */
@@ -110,19 +255,48 @@ async fn run() {
                 let now = std::time::Instant::now();
                 let dt = (now - last).as_secs_f32();
                 last = now;
-                time += dt;
-                scene.update(time, &queue);
-
-                // Actualizar rotación de la nave en órbita con flechas
-                cam.update_player_ship(dt, mouse_delta, &mut scene.planet_positions[2].0, &mut scene.ship_rot);
 
-                // Convertir posiciones de planetas a esferas de colisión
-                let collision_spheres: Vec<CollisionSphere> = scene.planet_positions
-                    .iter()
-                    .map(|(pos, radius)| CollisionSphere { center: *pos, radius: *radius })
-                    .collect();
+                /* ------ Acciones del teclado, ratón y mando ------ */
+                #[cfg(feature = "gamepad")]
+                if let Some(pad) = &mut gamepad {
+                    cam.analog = pad.poll(&mut input);
+                }
+                for (action, pressed) in input.take_actions() {
+                    match action {
+                        Action::GrabCursor if pressed && focused && !cursor_grabbed => {
+                            cursor_grabbed = grab_cursor(&window, true);
+                            grab_on_focus = true;
+                        }
+                        Action::ReleaseCursor if pressed && cursor_grabbed => {
+                            cursor_grabbed = grab_cursor(&window, false);
+                            grab_on_focus = false;
+                        }
+                        Action::SaveBookmark(slot) if pressed => {
+                            let bookmark = cam.capture(world.scene.planet_positions[2].0, world.scene.ship_rot, &world.scene.planet_positions);
+                            bookmarks.store(slot, bookmark);
+                            println!("Marcador {} guardado", slot);
+                        }
+                        Action::RecallBookmark(slot) if pressed => match bookmarks.get(slot) {
+                            Some(bookmark) => {
+                                cam.recall(bookmark, world.scene.planet_positions[2].0, &mut world.scene.ship_rot, &world.scene.planet_positions);
+                                println!("Marcador {}", slot);
+                            }
+                            None => println!("Marcador {} vacío", slot),
+                        },
+                        Action::AddKeyframe if pressed => {
+                            cam.add_keyframe(world.scene.planet_positions[2].0, world.scene.ship_rot);
+                        }
+                        _ => {}
+                    }
+                    cam.process_action(action, pressed);
+                    renderer.hdr.process_action(action, pressed);
+                    renderer.post.process_action(action, pressed);
+                    renderer.shadows.process_action(action, pressed);
+                    world.asteroids.propagator.process_action(action, pressed);
+                    world.scene.process_action(action, pressed);
+                }
 
-                cam.update_from_input(dt, mouse_delta, &collision_spheres);
+                world.update(&device, &queue, &mut cam, dt, mouse_delta, renderer.shadows.enabled);
                 mouse_delta = Vec2::ZERO;
 
                 let frame = match surface.get_current_texture() {
@@ -134,75 +308,7 @@ async fn run() {
                 };
 
                 let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
-                let aspect = config.width as f32 / config.height as f32;
-
-                let globals = Globals {
-                    view_proj: if cam.ship_view {
-                        cam.view_proj_from_ship(scene.planet_positions[2].0, scene.ship_rot, aspect).to_cols_array_2d()
-                    } else {
-                        cam.view_proj(aspect).to_cols_array_2d()
-                    },
-                    time,
-                    _pad0: [0.0; 3],
-                    _pad1: [0.0; 4],
-                    _pad2: [0.0; 4],
-                    _pad3: [0.0; 4],
-                };
-                queue.write_buffer(&renderer.globals_buf, 0, bytemuck::bytes_of(&globals));
-
-                // Actualizar posición de la nave en la cámara (la nave sigue a la cámara)
-                let camera_offset = Vec3::new(0.0, 0.0, 15.0); // Offset detrás de la cámara
-                let eye = Vec3::new(
-                    cam.target.x + cam.radius * cam.yaw.cos() * cam.pitch.cos(),
-                    cam.target.y + cam.radius * cam.pitch.sin(),
-                    cam.target.z + cam.radius * cam.yaw.sin() * cam.pitch.cos(),
-                );
-                let follow_ship_pos = eye - camera_offset;
-
-                let mut encoder =
-                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-
-                {
-                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                        label: Some("Main Pass"),
-                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                            view: &view,
-                            resolve_target: None,
-                            ops: wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(wgpu::Color {
-                                    r: 0.0,
-                                    g: 0.0,
-                                    b: 0.0,
-                                    a: 1.0,
-                                }),
-                                store: wgpu::StoreOp::Store,
-                            },
-                        })],
-                        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                            view: &renderer.depth_texture,
-                            depth_ops: Some(wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(1.0),
-                                store: wgpu::StoreOp::Store,
-                            }),
-                            stencil_ops: None,
-                        }),
-                        timestamp_writes: None,
-                        occlusion_query_set: None,
-                    });
-
-                    /* ------ Dibujar skybox/fondo con estrellas ------ */
-                    renderer.draw_skybox(&mut pass);
-
-                    /* ------ Dibujar planetas / modelos ------ */
-                    for model in &scene.models {
-                        renderer.draw_mesh(&mut pass, &model.vb, &model.ib, model.icount);
-                    }
-
-                    /* ------ Dibujar órbitas ------ */
-                    renderer.draw_orbits(&mut pass, &device, &scene.orbits);
-                }
-
-                queue.submit(Some(encoder.finish()));
+                world.render(&device, &queue, &mut renderer, &cam, &view, (config.width, config.height));
                 frame.present();
             }
 
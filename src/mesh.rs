@@ -1,4 +1,6 @@
-use glam::{Vec2, Vec3};
+use std::collections::HashMap;
+use glam::Vec3;
+use crate::spaceship::generate_spaceship;
 
 #[repr(C)]
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -24,14 +26,41 @@ impl Vertex {
     }
 }
 
-pub fn load_obj(path: &str, planet_id: u32) -> (Vec<Vertex>, Vec<u32>) {
+/// Geometría generada para un cuerpo sin OBJ (o cuyo OBJ falta)
+#[derive(Clone, Copy)]
+pub enum Shape {
+    UvSphere { stacks: u32, slices: u32 },
+    Icosphere { subdivisions: u32 },
+    CubeSphere { resolution: u32 }, // celdas por lado de cada cara
+    Spaceship,
+}
+
+/// Malla de `shape` con el tamaño de un cuerpo de radio `radius`
+pub fn generate_shape(shape: Shape, radius: f32, planet_id: u32) -> (Vec<Vertex>, Vec<u32>) {
+    match shape {
+        Shape::UvSphere { stacks, slices } => generate_uv_sphere(radius, stacks, slices, planet_id),
+        Shape::Icosphere { subdivisions } => generate_icosphere(radius, subdivisions, planet_id),
+        Shape::CubeSphere { resolution } => generate_cube_sphere(radius, resolution, planet_id),
+        Shape::Spaceship => {
+            // La nave generada mide 4 unidades desde el centro hasta la cola
+            let (mut verts, inds) = generate_spaceship();
+            for v in &mut verts {
+                v.pos = (Vec3::from(v.pos) * radius / 4.0).to_array();
+                v.planet_id = planet_id;
+            }
+            (verts, inds)
+        }
+    }
+}
+
+pub fn load_obj(path: &str, planet_id: u32) -> Result<(Vec<Vertex>, Vec<u32>), tobj::LoadError> {
     let (models, _) = tobj::load_obj(path, &tobj::LoadOptions {
         triangulate: true,
         single_index: true,
         ..Default::default()
-    }).expect("Failed to load OBJ");
+    })?;
 
-    let mesh = &models[0].mesh;
+    let mesh = &models.first().ok_or(tobj::LoadError::GenericFailure)?.mesh;
     let positions = &mesh.positions;
     let normals = &mesh.normals;
     let texcoords = &mesh.texcoords;
@@ -74,5 +103,187 @@ pub fn load_obj(path: &str, planet_id: u32) -> (Vec<Vertex>, Vec<u32>) {
         });
     }
 
-    (vertices, mesh.indices.clone())
-}
\ No newline at end of file
+    Ok((vertices, mesh.indices.clone()))
+}
+/// Esfera UV centrada en el origen, con `stacks` anillos de latitud y
+/// `slices` divisiones de longitud (los polos repiten vértices para la UV)
+pub fn generate_uv_sphere(radius: f32, stacks: u32, slices: u32, planet_id: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::with_capacity(((stacks + 1) * (slices + 1)) as usize);
+    for i in 0..=stacks {
+        let v = i as f32 / stacks as f32;
+        let theta = v * std::f32::consts::PI;
+        for j in 0..=slices {
+            let u = j as f32 / slices as f32;
+            let phi = u * std::f32::consts::TAU;
+            let n = [theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()];
+            vertices.push(Vertex {
+                pos: [n[0] * radius, n[1] * radius, n[2] * radius],
+                normal: n,
+                uv: [u, v],
+                planet_id,
+            });
+        }
+    }
+
+    let mut indices = Vec::with_capacity((stacks * slices * 6) as usize);
+    let row = slices + 1;
+    for i in 0..stacks {
+        for j in 0..slices {
+            let a = i * row + j;
+            let b = a + row;
+            // Antihorario visto desde fuera
+            indices.extend_from_slice(&[a, a + 1, b, a + 1, b + 1, b]);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Coordenadas equirectangulares de un punto de la esfera unitaria, con la
+/// misma convención que `generate_uv_sphere`
+fn sphere_uv(n: Vec3) -> [f32; 2] {
+    let u = n.z.atan2(n.x) / std::f32::consts::TAU;
+    let v = n.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI;
+    [u.rem_euclid(1.0), v]
+}
+
+/// Icosaedro subdividido `subdivisions` veces y proyectado a la esfera.
+/// Los triángulos que cruzan el cierre de la UV reciben vértices propios
+/// para que la textura no dé la vuelta entera. Desde una subdivisión hay
+/// vértices justo en los polos, donde la u no está definida: cada triángulo
+/// que los toca lleva su propia copia.
+pub fn generate_icosphere(radius: f32, subdivisions: u32, planet_id: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut points: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    // Cada arista se parte una sola vez aunque la compartan dos triángulos
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, points: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                points.push(((points[a as usize] + points[b as usize]) * 0.5).normalize());
+                points.len() as u32 - 1
+            })
+        };
+        let mut next = Vec::with_capacity(faces.len() * 4);
+        for [a, b, c] in faces {
+            let ab = midpoint(a, b, &mut points);
+            let bc = midpoint(b, c, &mut points);
+            let ca = midpoint(c, a, &mut points);
+            next.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+        faces = next;
+    }
+
+    let vertex = |n: Vec3, uv: [f32; 2]| Vertex {
+        pos: (n * radius).to_array(),
+        normal: n.to_array(),
+        uv,
+        planet_id,
+    };
+    let mut vertices: Vec<Vertex> = points.iter().map(|&n| vertex(n, sphere_uv(n))).collect();
+    let mut indices = Vec::with_capacity(faces.len() * 3);
+
+    for mut face in faces {
+        // Cruza el cierre: los vértices del lado u≈0 pasan a u+1
+        let us = face.map(|i| vertices[i as usize].uv[0]);
+        let max_u = us.iter().cloned().fold(0.0, f32::max);
+        let min_u = us.iter().cloned().fold(1.0, f32::min);
+        if max_u - min_u > 0.5 {
+            for k in 0..3 {
+                if us[k] < 0.5 {
+                    let original = vertices[face[k] as usize];
+                    vertices.push(Vertex { uv: [us[k] + 1.0, original.uv[1]], ..original });
+                    face[k] = vertices.len() as u32 - 1;
+                }
+            }
+        }
+        // En el polo la u es la media de la de los otros dos vértices, así
+        // la textura no se retuerce en ese triángulo
+        for k in 0..3 {
+            let pole = vertices[face[k] as usize];
+            if pole.normal[0].abs() < 1e-6 && pole.normal[2].abs() < 1e-6 {
+                let u = (vertices[face[(k + 1) % 3] as usize].uv[0] + vertices[face[(k + 2) % 3] as usize].uv[0]) * 0.5;
+                vertices.push(Vertex { uv: [u, pole.uv[1]], ..pole });
+                face[k] = vertices.len() as u32 - 1;
+            }
+        }
+        indices.extend_from_slice(&face);
+    }
+
+    (vertices, indices)
+}
+
+/// Caras del cubo como (normal, eje u); el eje v sale de normal × u para
+/// que los triángulos queden en sentido antihorario vistos desde fuera
+pub const CUBE_FACES: [(Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::NEG_Z),
+    (Vec3::NEG_X, Vec3::Z),
+    (Vec3::Y, Vec3::X),
+    (Vec3::NEG_Y, Vec3::X),
+    (Vec3::Z, Vec3::X),
+    (Vec3::NEG_Z, Vec3::NEG_X),
+];
+
+/// Punto (s, t) en 0..1 de una cara del cubo proyectado a la esfera unitaria
+/// con la transformación de área casi uniforme
+pub fn cube_to_sphere(face: usize, s: f32, t: f32) -> Vec3 {
+    let (normal, axis_u) = CUBE_FACES[face];
+    let axis_v = normal.cross(axis_u);
+    let c = normal + axis_u * (2.0 * s - 1.0) + axis_v * (2.0 * t - 1.0);
+    let (x2, y2, z2) = (c.x * c.x, c.y * c.y, c.z * c.z);
+    Vec3::new(
+        c.x * (1.0 - y2 / 2.0 - z2 / 2.0 + y2 * z2 / 3.0).sqrt(),
+        c.y * (1.0 - z2 / 2.0 - x2 / 2.0 + z2 * x2 / 3.0).sqrt(),
+        c.z * (1.0 - x2 / 2.0 - y2 / 2.0 + x2 * y2 / 3.0).sqrt(),
+    )
+}
+
+/// Cubo de `resolution` x `resolution` celdas por cara proyectado a la
+/// esfera. Cada cara tiene su propia UV en 0..1, sin polos ni cierre.
+pub fn generate_cube_sphere(radius: f32, resolution: u32, planet_id: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let row = resolution + 1;
+    let mut vertices = Vec::with_capacity((6 * row * row) as usize);
+    let mut indices = Vec::with_capacity((6 * resolution * resolution * 6) as usize);
+
+    for face in 0..CUBE_FACES.len() {
+        let base = vertices.len() as u32;
+
+        for j in 0..=resolution {
+            for i in 0..=resolution {
+                let s = i as f32 / resolution as f32;
+                let t = j as f32 / resolution as f32;
+                let n = cube_to_sphere(face, s, t);
+                vertices.push(Vertex {
+                    pos: (n * radius).to_array(),
+                    normal: n.to_array(),
+                    uv: [s, t],
+                    planet_id,
+                });
+            }
+        }
+
+        for j in 0..resolution {
+            for i in 0..resolution {
+                let a = base + j * row + i;
+                indices.extend_from_slice(&[a, a + 1, a + row, a + 1, a + row + 1, a + row]);
+            }
+        }
+    }
+
+    (vertices, indices)
+}
@@ -0,0 +1,315 @@
+use glam::Vec3;
+use serde::Deserialize;
+use crate::propagator::{KeplerElements, MAX_SIM_STEP};
+use crate::scene::SUN_MU;
+
+/// Escena de N cuerpos leída de un archivo TOML. Cada cuerpo de la escena
+/// (por su índice en `planet_positions`) recibe una masa y un estado inicial,
+/// ya sea con elementos keplerianos respecto a su padre o con posición y
+/// velocidad explícitas. Los padres deben aparecer antes que sus satélites.
+#[derive(Deserialize)]
+pub struct NBodyScene {
+    #[serde(default = "default_softening")]
+    pub softening: f32,
+    #[serde(default = "default_substeps")]
+    pub substeps: u32,
+    #[serde(rename = "body")]
+    pub bodies: Vec<BodySpec>,
+}
+
+#[derive(Deserialize)]
+pub struct BodySpec {
+    pub body: usize,
+    #[serde(default)]
+    pub mass: Option<f32>, // G·m; el sol (cuerpo 0) tiene SUN_MU por defecto
+    #[serde(default)]
+    pub parent: Option<usize>, // por defecto el sol si hay `orbit`
+    #[serde(default)]
+    pub orbit: Option<OrbitSpec>,
+    #[serde(default)]
+    pub position: [f32; 3], // relativos al padre, si lo hay
+    #[serde(default)]
+    pub velocity: [f32; 3],
+}
+
+/// Elementos keplerianos con los ángulos en grados, más cómodos de escribir
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct OrbitSpec {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub inclination: f32,
+    pub node: f32,
+    pub periapsis: f32,
+    pub mean_anomaly: f32,
+}
+
+fn default_softening() -> f32 {
+    0.2
+}
+
+fn default_substeps() -> u32 {
+    8
+}
+
+impl OrbitSpec {
+    fn elements(&self) -> KeplerElements {
+        KeplerElements {
+            semi_major_axis: self.semi_major_axis,
+            eccentricity: self.eccentricity,
+            inclination: self.inclination.to_radians(),
+            node: self.node.to_radians(),
+            periapsis: self.periapsis.to_radians(),
+            mean_anomaly: self.mean_anomaly.to_radians(),
+        }
+    }
+}
+
+impl NBodyScene {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&text).map_err(|e| e.to_string())
+    }
+}
+
+struct SimBody {
+    body: usize,
+    mass: f32,
+    radius: f32,
+    pos: Vec3,
+    vel: Vec3,
+    acc: Vec3,
+    merged_into: Option<usize>, // índice del cuerpo que lo absorbió
+}
+
+/// Choque entre dos cuerpos, para los efectos (restos, destellos...)
+pub struct Collision {
+    pub position: Vec3, // punto de contacto
+    pub velocity: Vec3, // del cuerpo resultante
+    pub radius: f32,    // del cuerpo absorbido
+}
+
+/// Integración leapfrog (kick-drift-kick) de la gravedad mutua de los
+/// cuerpos de la escena. Es simpléctica: el error de energía oscila en vez
+/// de acumularse, así que las órbitas no se abren con el tiempo. Dos cuerpos
+/// que se tocan se funden en el más pesado conservando el momento.
+pub struct NBodySim {
+    bodies: Vec<SimBody>,
+    softening: f32,
+    substeps: u32,
+    collisions: Vec<Collision>,
+}
+
+impl NBodySim {
+    /// Estado inicial de la escena; `radii` son los radios de los cuerpos
+    pub fn new(scene: &NBodyScene, radii: &[f32]) -> Result<Self, String> {
+        let mut bodies: Vec<SimBody> = Vec::with_capacity(scene.bodies.len());
+        for spec in &scene.bodies {
+            if spec.body >= radii.len() {
+                return Err(format!("el cuerpo {} no existe en la escena", spec.body));
+            }
+            if bodies.iter().any(|b| b.body == spec.body) {
+                return Err(format!("el cuerpo {} aparece dos veces", spec.body));
+            }
+            let mass = match (spec.mass, spec.body) {
+                (Some(mass), _) => mass,
+                (None, 0) => SUN_MU,
+                (None, _) => return Err(format!("falta la masa del cuerpo {}", spec.body)),
+            };
+
+            let parent = match spec.parent.or(spec.orbit.as_ref().map(|_| 0)) {
+                Some(p) => Some(
+                    bodies
+                        .iter()
+                        .find(|b| b.body == p)
+                        .ok_or(format!("el padre {} del cuerpo {} debe ir antes", p, spec.body))?,
+                ),
+                None => None,
+            };
+            let (origin, origin_vel, parent_mass) = parent.map_or((Vec3::ZERO, Vec3::ZERO, 0.0), |p| (p.pos, p.vel, p.mass));
+
+            let (pos, vel) = match &spec.orbit {
+                Some(orbit) => {
+                    if orbit.semi_major_axis <= 0.0 || !(0.0..1.0).contains(&orbit.eccentricity) {
+                        return Err(format!("órbita no válida para el cuerpo {}", spec.body));
+                    }
+                    orbit.elements().state_vectors(parent_mass + mass, 0.0)
+                }
+                None => (Vec3::from(spec.position), Vec3::from(spec.velocity)),
+            };
+
+            bodies.push(SimBody {
+                body: spec.body,
+                mass,
+                radius: radii[spec.body],
+                pos: origin + pos,
+                vel: origin_vel + vel,
+                acc: Vec3::ZERO,
+                merged_into: None,
+            });
+        }
+
+        // Sistema en reposo: sin esto el conjunto deriva con el momento del sol
+        let total: f32 = bodies.iter().map(|b| b.mass).sum();
+        if total > 0.0 {
+            let drift = bodies.iter().map(|b| b.vel * b.mass).sum::<Vec3>() / total;
+            for b in &mut bodies {
+                b.vel -= drift;
+            }
+        }
+
+        let mut sim = Self {
+            bodies,
+            softening: scene.softening,
+            substeps: scene.substeps.max(1),
+            collisions: Vec::new(),
+        };
+        sim.compute_accelerations();
+        Ok(sim)
+    }
+
+    /// El cuerpo se mueve por la simulación en vez de por su órbita fija
+    pub fn simulates(&self, body: usize) -> bool {
+        self.bodies.iter().any(|b| b.body == body)
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        let h = dt.min(MAX_SIM_STEP) / self.substeps as f32;
+        for _ in 0..self.substeps {
+            for b in self.bodies.iter_mut().filter(|b| b.merged_into.is_none()) {
+                b.vel += b.acc * (h * 0.5);
+                b.pos += b.vel * h;
+            }
+            self.compute_accelerations();
+            for b in self.bodies.iter_mut().filter(|b| b.merged_into.is_none()) {
+                b.vel += b.acc * (h * 0.5);
+            }
+            if self.merge_collisions() {
+                self.compute_accelerations();
+            }
+        }
+    }
+
+    /// (cuerpo, posición) de cada cuerpo simulado; los absorbidos siguen al
+    /// que los absorbió
+    pub fn positions(&self) -> impl Iterator<Item = (usize, Vec3)> + '_ {
+        self.bodies.iter().map(|b| {
+            let mut root = b;
+            while let Some(i) = root.merged_into {
+                root = &self.bodies[i];
+            }
+            (b.body, root.pos)
+        })
+    }
+
+    /// Choques desde la última llamada
+    pub fn take_collisions(&mut self) -> Vec<Collision> {
+        std::mem::take(&mut self.collisions)
+    }
+
+    fn compute_accelerations(&mut self) {
+        let eps2 = self.softening * self.softening;
+        for i in 0..self.bodies.len() {
+            if self.bodies[i].merged_into.is_some() {
+                continue;
+            }
+            let x = self.bodies[i].pos;
+            let acc = self
+                .bodies
+                .iter()
+                .enumerate()
+                .filter(|&(j, b)| j != i && b.merged_into.is_none())
+                .map(|(_, b)| {
+                    let d = b.pos - x;
+                    let d2 = d.length_squared() + eps2;
+                    d * (b.mass / (d2 * d2.sqrt()))
+                })
+                .sum();
+            self.bodies[i].acc = acc;
+        }
+    }
+
+    /// Devuelve si hubo alguna fusión
+    fn merge_collisions(&mut self) -> bool {
+        let mut merged = false;
+        let n = self.bodies.len();
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let (a, b) = (&self.bodies[i], &self.bodies[j]);
+                if a.merged_into.is_some() || b.merged_into.is_some() {
+                    continue;
+                }
+                if a.pos.distance(b.pos) > a.radius + b.radius {
+                    continue;
+                }
+
+                let (keep, lost) = if a.mass >= b.mass { (i, j) } else { (j, i) };
+                let (k, l) = (&self.bodies[keep], &self.bodies[lost]);
+                let mass = k.mass + l.mass;
+                // Dos cuerpos sin masa no cambian la trayectoria del que queda
+                let (pos, vel) = if mass > 0.0 {
+                    ((k.pos * k.mass + l.pos * l.mass) / mass, (k.vel * k.mass + l.vel * l.mass) / mass)
+                } else {
+                    (k.pos, k.vel)
+                };
+                println!("Colisión: el cuerpo {} absorbe al cuerpo {}", k.body, l.body);
+                let contact = l.pos + (k.pos - l.pos).normalize_or_zero() * l.radius;
+                self.collisions.push(Collision { position: contact, velocity: vel, radius: l.radius });
+
+                let k = &mut self.bodies[keep];
+                k.mass = mass;
+                k.pos = pos;
+                k.vel = vel;
+                self.bodies[lost].merged_into = Some(keep);
+                merged = true;
+            }
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(body: usize, mass: Option<f32>, position: [f32; 3], velocity: [f32; 3]) -> BodySpec {
+        BodySpec { body, mass, parent: None, orbit: None, position, velocity }
+    }
+
+    fn momentum(sim: &NBodySim) -> Vec3 {
+        sim.bodies.iter().filter(|b| b.merged_into.is_none()).map(|b| b.vel * b.mass).sum()
+    }
+
+    #[test]
+    fn momentum_conserved_across_merge() {
+        let scene = NBodyScene {
+            softening: 0.2,
+            substeps: 8,
+            bodies: vec![
+                body(0, Some(5.0), [-3.0, 0.0, 0.0], [2.0, 0.5, 0.0]),
+                body(1, Some(1.0), [3.0, 0.2, 0.0], [-4.0, 0.0, 1.0]),
+                body(2, Some(0.5), [0.0, 8.0, 0.0], [0.0, 0.0, -1.0]),
+            ],
+        };
+        let mut sim = NBodySim::new(&scene, &[1.0, 1.0, 0.5]).unwrap();
+        let before = momentum(&sim);
+        for _ in 0..60 {
+            sim.step(1.0 / 30.0);
+        }
+        assert_eq!(sim.take_collisions().len(), 1);
+        assert!(sim.bodies[1].merged_into.is_some());
+        assert!(momentum(&sim).distance(before) < 1e-3);
+        // El absorbido sigue al que lo absorbió
+        let positions: Vec<_> = sim.positions().collect();
+        assert_eq!(positions[0].1, positions[1].1);
+    }
+
+    #[test]
+    fn sun_mass_defaults_to_sun_mu() {
+        let scene = NBodyScene { softening: 0.2, substeps: 8, bodies: vec![body(0, None, [0.0; 3], [0.0; 3])] };
+        assert_eq!(NBodySim::new(&scene, &[1.0]).unwrap().bodies[0].mass, SUN_MU);
+
+        let scene = NBodyScene { softening: 0.2, substeps: 8, bodies: vec![body(1, None, [0.0; 3], [0.0; 3])] };
+        assert!(NBodySim::new(&scene, &[1.0, 1.0]).is_err());
+    }
+}
@@ -1,4 +1,27 @@
+use bytemuck::Zeroable;
 use glam::Vec3;
+use wgpu::util::DeviceExt;
+
+/// Segmentos con los que se dibuja cada órbita
+pub const ORBIT_SEGMENTS: u32 = 128;
+
+/// Estilo con el que se dibuja la línea de una órbita
+#[derive(Clone, Copy)]
+pub struct OrbitStyle {
+    pub color: [f32; 4],
+    pub width: f32, // grosor en píxeles
+    pub fade: bool, // se desvanece lejos del cuerpo
+}
+
+/// Órbita circular de un cuerpo alrededor del origen o de otro cuerpo
+#[derive(Clone, Copy)]
+pub struct Orbit {
+    pub body: usize,           // índice del cuerpo que la recorre
+    pub parent: Option<usize>, // cuerpo en el centro (None = sol en el origen)
+    pub radius: f32,
+    pub speed: f32,            // velocidad angular (rad/s)
+    pub line: Option<OrbitStyle>,
+}
 
 pub fn generate_orbit(radius: f32, segments: u32) -> Vec<Vec3> {
     let mut pts = Vec::new();
@@ -7,4 +30,162 @@ pub fn generate_orbit(radius: f32, segments: u32) -> Vec<Vec3> {
         pts.push(Vec3::new(t.cos() * radius, 0.0, t.sin() * radius));
     }
     pts
-}
\ No newline at end of file
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct OrbitPoint {
+    pub pos: [f32; 3], // relativo al centro de la órbita
+    pub orbit: u32,
+}
+
+impl OrbitPoint {
+    /// Cada segmento es una instancia: el mismo buffer se enlaza dos veces,
+    /// desplazado un punto, para leer los extremos `a` y `b`.
+    pub fn segment_layouts() -> [wgpu::VertexBufferLayout<'static>; 2] {
+        let stride = std::mem::size_of::<OrbitPoint>() as wgpu::BufferAddress;
+        [
+            wgpu::VertexBufferLayout {
+                array_stride: stride,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                    0 => Float32x3,  // a.pos
+                    1 => Uint32      // a.orbit
+                ])),
+            },
+            wgpu::VertexBufferLayout {
+                array_stride: stride,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                    2 => Float32x3,  // b.pos
+                    3 => Uint32      // b.orbit
+                ])),
+            },
+        ]
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct OrbitParams {
+    color: [f32; 4],
+    center: [f32; 4],   // w = radio
+    body_pos: [f32; 4], // w = 1.0 si la órbita se desvanece
+    width: f32,         // grosor en píxeles
+    _pad: [f32; 3],
+}
+
+/// Líneas de órbita residentes en la GPU. Los puntos se crean una sola vez
+/// y solo se reescriben cuando cambia el radio de una órbita; el centro y la
+/// posición del cuerpo viajan en un buffer de parámetros pequeño.
+///
+/// Se dibujan como quads expandidos en pantalla, una instancia por segmento
+/// entre puntos consecutivos. El segmento que une el último punto de una
+/// órbita con el primero de la siguiente se descarta en el shader.
+pub struct GpuOrbits {
+    vb: wgpu::Buffer,
+    segment_count: u32,
+    params_buf: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    // (índice en scene.orbits, radio con el que se generaron los vértices)
+    lines: Vec<(usize, f32)>,
+}
+
+impl GpuOrbits {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, orbits: &[Orbit]) -> Self {
+        let lines: Vec<(usize, f32)> = orbits
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.line.is_some())
+            .map(|(i, o)| (i, o.radius))
+            .collect();
+
+        let points: Vec<OrbitPoint> = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(line, (_, radius))| Self::orbit_points(line as u32, *radius))
+            .collect();
+
+        let vb = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Orbit Points"),
+            contents: bytemuck::cast_slice(&points),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Al menos un elemento para que el binding de storage sea válido
+        let params = vec![OrbitParams::zeroed(); lines.len().max(1)];
+        let params_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Orbit Params"),
+            contents: bytemuck::cast_slice(&params),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buf.as_entire_binding(),
+            }],
+            label: Some("Orbit BG"),
+        });
+
+        Self {
+            vb,
+            segment_count: points.len().saturating_sub(1) as u32,
+            params_buf,
+            bind_group,
+            lines,
+        }
+    }
+
+    fn orbit_points(line: u32, radius: f32) -> Vec<OrbitPoint> {
+        generate_orbit(radius, ORBIT_SEGMENTS)
+            .iter()
+            .map(|p| OrbitPoint { pos: p.to_array(), orbit: line })
+            .collect()
+    }
+
+    /// Actualiza centros y posiciones de los cuerpos, y regenera los vértices
+    /// de las órbitas cuyo radio haya cambiado.
+    pub fn sync(&mut self, queue: &wgpu::Queue, orbits: &[Orbit], positions: &[(Vec3, f32)]) {
+        let points_per_line = (ORBIT_SEGMENTS + 1) as u64;
+        let mut params = Vec::with_capacity(self.lines.len());
+
+        for (line, (idx, cached_radius)) in self.lines.iter_mut().enumerate() {
+            let orbit = &orbits[*idx];
+            let style = orbit.line.expect("órbita sin estilo de línea");
+
+            if orbit.radius != *cached_radius {
+                *cached_radius = orbit.radius;
+                let points = Self::orbit_points(line as u32, orbit.radius);
+                let offset = line as u64 * points_per_line * std::mem::size_of::<OrbitPoint>() as u64;
+                queue.write_buffer(&self.vb, offset, bytemuck::cast_slice(&points));
+            }
+
+            let center = orbit.parent.map_or(Vec3::ZERO, |p| positions[p].0);
+            let body = positions[orbit.body].0;
+            params.push(OrbitParams {
+                color: style.color,
+                center: [center.x, center.y, center.z, orbit.radius],
+                body_pos: [body.x, body.y, body.z, if style.fade { 1.0 } else { 0.0 }],
+                width: style.width,
+                _pad: [0.0; 3],
+            });
+        }
+
+        if !params.is_empty() {
+            queue.write_buffer(&self.params_buf, 0, bytemuck::cast_slice(&params));
+        }
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if self.segment_count == 0 {
+            return;
+        }
+        let stride = std::mem::size_of::<OrbitPoint>() as wgpu::BufferAddress;
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.vb.slice(..));
+        pass.set_vertex_buffer(1, self.vb.slice(stride..));
+        pass.draw(0..6, 0..self.segment_count);
+    }
+}
@@ -0,0 +1,386 @@
+use std::borrow::Cow;
+use glam::Vec3;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::hdr::HDR_FORMAT;
+
+/// Capacidad del buffer circular: al llenarse, las partículas nuevas
+/// sustituyen a las más antiguas
+pub const MAX_PARTICLES: u32 = 16384;
+/// Esferas que suavizan el corte de las partículas (ver fs_particle)
+const MAX_OCCLUDERS: usize = 16;
+/// Prefijo WGSL de los billboards aditivos, que también usan los cometas
+const BILLBOARD_WGSL: &str = include_str!("shader_billboard.wgsl");
+
+/// Shader `source` con las funciones de billboard de shader_billboard.wgsl
+pub fn billboard_shader<'a>(label: &'a str, source: &str) -> wgpu::ShaderModuleDescriptor<'a> {
+    wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(Cow::Owned(format!("{}\n{}", BILLBOARD_WGSL, source))),
+    }
+}
+
+/// Cómo nacen y evolucionan las partículas de un tipo de efecto. Los pares
+/// son (mínimo, máximo) para los valores aleatorios y (inicio, fin) para los
+/// que cambian con la edad.
+#[derive(Clone, Copy)]
+pub struct EmitterDef {
+    pub rate: f32, // partículas por segundo en emisión continua
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    pub spread: f32, // semiángulo del cono de salida; PI = en todas direcciones
+    pub size: (f32, f32),
+    pub color: ([f32; 3], [f32; 3]), // HDR, la mezcla es aditiva
+    pub drag: f32,    // frenado por segundo
+    pub stretch: f32, // alargamiento según la velocidad
+}
+
+/// Chorro del propulsor de la nave
+pub const EXHAUST: EmitterDef = EmitterDef {
+    rate: 300.0,
+    lifetime: (0.2, 0.5),
+    speed: (3.0, 5.0),
+    spread: 0.15,
+    size: (0.06, 0.2),
+    color: ([0.6, 0.35, 0.12], [0.12, 0.03, 0.01]),
+    drag: 1.5,
+    stretch: 0.03,
+};
+
+/// Restos que salen despedidos en una colisión
+pub const DEBRIS: EmitterDef = EmitterDef {
+    rate: 0.0,
+    lifetime: (2.0, 5.0),
+    speed: (1.0, 6.0),
+    spread: std::f32::consts::PI,
+    size: (0.2, 0.06),
+    color: ([2.5, 1.2, 0.5], [0.15, 0.1, 0.08]),
+    drag: 0.3,
+    stretch: 0.1,
+};
+
+/// Viento solar: partículas tenues que se alejan del sol
+pub const SOLAR_WIND: EmitterDef = EmitterDef {
+    rate: 120.0,
+    lifetime: (8.0, 12.0),
+    speed: (4.0, 7.0),
+    spread: std::f32::consts::PI,
+    size: (0.3, 0.9),
+    color: ([0.15, 0.11, 0.05], [0.0, 0.02, 0.05]),
+    drag: 0.0,
+    stretch: 0.05,
+};
+
+/// Emisor continuo: guarda la fracción de partícula que sobra de cada frame
+pub struct Emitter {
+    pub def: EmitterDef,
+    carry: f32,
+}
+
+impl Emitter {
+    pub fn new(def: EmitterDef) -> Self {
+        Self { def, carry: 0.0 }
+    }
+
+    /// Partículas que tocan en este frame
+    pub fn count(&mut self, dt: f32) -> u32 {
+        self.carry += self.def.rate * dt;
+        let n = self.carry.floor();
+        self.carry -= n;
+        n as u32
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Particle {
+    position: [f32; 4],    // w = edad
+    velocity: [f32; 4],    // w = vida total
+    color_start: [f32; 4], // w = tamaño inicial
+    color_end: [f32; 4],   // w = tamaño final
+    motion: [f32; 4],      // frenado, alargamiento
+}
+
+impl Particle {
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Particle>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                0 => Float32x4,
+                1 => Float32x4,
+                2 => Float32x4,
+                3 => Float32x4,
+                4 => Float32x4
+            ])),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct UpdateParams {
+    dt: f32,
+    count: u32,
+    _pad: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Occluders {
+    spheres: [[f32; 4]; MAX_OCCLUDERS], // centro + radio
+    count: u32,
+    _pad: [u32; 3],
+}
+
+/// Sistema de partículas reutilizable. La CPU solo escribe las partículas
+/// que nacen, en un buffer circular; un compute shader las mueve cada frame
+/// y el mismo buffer se dibuja como instancias de billboards aditivos.
+///
+/// El suavizado contra la geometría no lee el depth (en GL no se puede leer
+/// el depth MSAA): se calcula con las esferas de los cuerpos, que es lo
+/// único contra lo que las partículas se cruzan de forma visible.
+pub struct ParticleSystem {
+    particle_buf: wgpu::Buffer,
+    params_buf: wgpu::Buffer,
+    occluder_buf: wgpu::Buffer,
+    update_bg: wgpu::BindGroup,
+    render_bg: wgpu::BindGroup,
+    update_pipeline: wgpu::ComputePipeline,
+    render_pipeline: wgpu::RenderPipeline,
+    head: u32, // siguiente hueco del buffer circular
+    rng: StdRng,
+}
+
+impl ParticleSystem {
+    pub fn new(device: &wgpu::Device, globals_layout: &wgpu::BindGroupLayout, sample_count: u32) -> Self {
+        let particle_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particles"),
+            size: MAX_PARTICLES as u64 * std::mem::size_of::<Particle>() as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false, // a cero: edad 0 con vida 0 es una partícula muerta
+        });
+        let params_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Params"),
+            size: std::mem::size_of::<UpdateParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let occluder_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Occluders"),
+            size: std::mem::size_of::<Occluders>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_entry = |binding, visibility| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        // El compute usa bindings distintos de los del render para poder
+        // compartir el módulo de shader
+        let update_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle Update Layout"),
+            entries: &[
+                uniform_entry(1, wgpu::ShaderStages::COMPUTE),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let update_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particle Update BG"),
+            layout: &update_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 1, resource: params_buf.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: particle_buf.as_entire_binding() },
+            ],
+        });
+
+        let render_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle Render Layout"),
+            entries: &[uniform_entry(0, wgpu::ShaderStages::FRAGMENT)],
+        });
+        let render_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particle Render BG"),
+            layout: &render_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: occluder_buf.as_entire_binding() }],
+        });
+
+        let shader = device.create_shader_module(billboard_shader("shader_particles.wgsl", include_str!("shader_particles.wgsl")));
+
+        let update_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Particle Update Pipeline"),
+            layout: Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Particle Update Pipeline Layout"),
+                bind_group_layouts: &[&update_layout],
+                push_constant_ranges: &[],
+            })),
+            module: &shader,
+            entry_point: "cs_update",
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Particle Pipeline"),
+            layout: Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Particle Pipeline Layout"),
+                bind_group_layouts: &[globals_layout, &render_layout],
+                push_constant_ranges: &[],
+            })),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_particle",
+                buffers: &[Particle::layout()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_particle",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::OVER,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            particle_buf,
+            params_buf,
+            occluder_buf,
+            update_bg,
+            render_bg,
+            update_pipeline,
+            render_pipeline,
+            head: 0,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    /// Emite `count` partículas desde la esfera de radio `radius` en `origin`,
+    /// en un cono alrededor de `dir` (cualquier dirección si el cono es
+    /// completo), sumando `base_velocity` a su velocidad
+    #[allow(clippy::too_many_arguments)]
+    pub fn emit(
+        &mut self,
+        queue: &wgpu::Queue,
+        def: &EmitterDef,
+        count: u32,
+        origin: Vec3,
+        radius: f32,
+        dir: Vec3,
+        base_velocity: Vec3,
+    ) {
+        let count = count.min(MAX_PARTICLES);
+        if count == 0 {
+            return;
+        }
+        let axis = dir.try_normalize().unwrap_or(Vec3::Y);
+        let (tangent, bitangent) = axis.any_orthonormal_pair();
+        let cos_spread = def.spread.cos();
+
+        let particles: Vec<Particle> = (0..count)
+            .map(|_| {
+                // Dirección uniforme dentro del cono
+                let z = self.rng.gen_range(cos_spread..=1.0);
+                let phi = self.rng.gen_range(0.0..std::f32::consts::TAU);
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                let d = axis * z + (tangent * phi.cos() + bitangent * phi.sin()) * r;
+
+                let pos = origin + d * radius;
+                let vel = base_velocity + d * self.rng.gen_range(def.speed.0..=def.speed.1);
+                let (c0, c1) = def.color;
+                Particle {
+                    position: pos.extend(0.0).to_array(),
+                    velocity: vel.extend(self.rng.gen_range(def.lifetime.0..=def.lifetime.1)).to_array(),
+                    color_start: [c0[0], c0[1], c0[2], def.size.0],
+                    color_end: [c1[0], c1[1], c1[2], def.size.1],
+                    motion: [def.drag, def.stretch, 0.0, 0.0],
+                }
+            })
+            .collect();
+
+        // Hasta dos escrituras si el bloque da la vuelta al buffer circular
+        let stride = std::mem::size_of::<Particle>() as u64;
+        let first = (MAX_PARTICLES - self.head).min(count) as usize;
+        queue.write_buffer(&self.particle_buf, self.head as u64 * stride, bytemuck::cast_slice(&particles[..first]));
+        if first < particles.len() {
+            queue.write_buffer(&self.particle_buf, 0, bytemuck::cast_slice(&particles[first..]));
+        }
+        self.head = (self.head + count) % MAX_PARTICLES;
+    }
+
+    /// Esferas de los cuerpos, para el suavizado al cruzarse con ellos
+    pub fn set_occluders(&self, queue: &wgpu::Queue, bodies: &[(Vec3, f32)]) {
+        let mut occluders = Occluders {
+            spheres: [[0.0; 4]; MAX_OCCLUDERS],
+            count: bodies.len().min(MAX_OCCLUDERS) as u32,
+            _pad: [0; 3],
+        };
+        for (s, &(center, radius)) in occluders.spheres.iter_mut().zip(bodies) {
+            *s = center.extend(radius).to_array();
+        }
+        queue.write_buffer(&self.occluder_buf, 0, bytemuck::bytes_of(&occluders));
+    }
+
+    /// Avanza todas las partículas `dt` segundos
+    pub fn update(&self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, dt: f32) {
+        let params = UpdateParams { dt, count: MAX_PARTICLES, _pad: [0; 2] };
+        queue.write_buffer(&self.params_buf, 0, bytemuck::bytes_of(&params));
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Particle Update Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.update_pipeline);
+        pass.set_bind_group(0, &self.update_bg, &[]);
+        pass.dispatch_workgroups(MAX_PARTICLES.div_ceil(64), 1, 1);
+    }
+
+    /// Dibuja las partículas en el pase principal, después de los opacos
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, globals_bg: &'a wgpu::BindGroup) {
+        pass.set_pipeline(&self.render_pipeline);
+        pass.set_bind_group(0, globals_bg, &[]);
+        pass.set_bind_group(1, &self.render_bg, &[]);
+        pass.set_vertex_buffer(0, self.particle_buf.slice(..));
+        pass.draw(0..6, 0..MAX_PARTICLES);
+    }
+}
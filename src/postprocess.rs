@@ -0,0 +1,328 @@
+use std::borrow::Cow;
+use crate::input::Action;
+
+/// Prefijo WGSL compartido: triángulo de pantalla completa y bindings
+const COMMON_WGSL: &str = include_str!("shader_post_common.wgsl");
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostUniforms {
+    params: [f32; 4],
+    resolution: [f32; 2],
+    time: f32,
+    _pad: f32,
+}
+
+/// Parámetro de un efecto ajustable en marcha: paso de cada pulsación y rango
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub step: f32,
+    pub range: (f32, f32),
+}
+
+const GRADING_PARAMS: &[ParamInfo] = &[
+    ParamInfo { name: "contraste", step: 0.05, range: (0.0, 3.0) },
+    ParamInfo { name: "saturación", step: 0.05, range: (0.0, 3.0) },
+    ParamInfo { name: "temperatura", step: 0.05, range: (-1.0, 1.0) },
+];
+const CHROMATIC_PARAMS: &[ParamInfo] = &[
+    ParamInfo { name: "desplazamiento (px)", step: 0.5, range: (0.0, 20.0) },
+];
+const VIGNETTE_PARAMS: &[ParamInfo] = &[
+    ParamInfo { name: "intensidad", step: 0.05, range: (0.0, 1.0) },
+    ParamInfo { name: "radio", step: 0.05, range: (0.0, 1.5) },
+    // Con suavidad 0 el smoothstep del shader no está definido
+    ParamInfo { name: "suavidad", step: 0.05, range: (0.01, 1.0) },
+];
+const GRAIN_PARAMS: &[ParamInfo] = &[
+    ParamInfo { name: "intensidad", step: 0.01, range: (0.0, 0.5) },
+];
+
+/// Un efecto de pantalla completa con su propio shader y parámetros
+pub struct PostEffect {
+    pub name: &'static str,
+    pub enabled: bool,
+    pub params: [f32; 4], // significado propio de cada shader
+    pub param_info: &'static [ParamInfo], // los que usa, en orden
+    pipeline: wgpu::RenderPipeline,
+    uniform_buf: wgpu::Buffer,
+    bind_groups: [wgpu::BindGroup; 2], // lee de la textura intermedia 0 o 1
+}
+
+/// Cadena ordenada de efectos aplicada tras el tone mapping. Los efectos
+/// activos se encadenan sobre dos texturas intermedias y el último escribe
+/// directamente en la salida.
+pub struct PostChain {
+    pub enabled: bool, // desactivar toda la cadena para comparar A/B
+    pub effects: Vec<PostEffect>,
+    selected: (usize, usize), // efecto y parámetro que se ajustan
+    targets: [wgpu::TextureView; 2],
+    width: u32,
+    height: u32,
+}
+
+impl PostChain {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let make_target = || {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Post Target"),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+        let targets = [make_target(), make_target()];
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Pipeline Layout"),
+            bind_group_layouts: &[&bg_layout],
+            push_constant_ranges: &[],
+        });
+
+        let make_effect = |name: &'static str, source: &str, enabled: bool, params: [f32; 4], param_info: &'static [ParamInfo]| {
+            let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(name),
+                source: wgpu::ShaderSource::Wgsl(Cow::Owned(format!("{}\n{}", COMMON_WGSL, source))),
+            });
+
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(name),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_fullscreen",
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+            let uniform_buf = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Post Uniforms"),
+                size: std::mem::size_of::<PostUniforms>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let bind_groups = [0, 1].map(|i| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Post BG"),
+                    layout: &bg_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&targets[i]),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: uniform_buf.as_entire_binding(),
+                        },
+                    ],
+                })
+            });
+
+            PostEffect {
+                name,
+                enabled,
+                params,
+                param_info,
+                pipeline,
+                uniform_buf,
+                bind_groups,
+            }
+        };
+
+        // Orden de aplicación; F1..F4 activan/desactivan cada uno y F5 elige
+        // el parámetro que cambian RePág/AvPág
+        let effects = vec![
+            make_effect("Color Grading", include_str!("shader_post_grading.wgsl"), true, [1.05, 1.1, 0.2, 0.0], GRADING_PARAMS),
+            make_effect("Chromatic Aberration", include_str!("shader_post_chromatic.wgsl"), false, [3.0, 0.0, 0.0, 0.0], CHROMATIC_PARAMS),
+            make_effect("Vignette", include_str!("shader_post_vignette.wgsl"), true, [0.6, 0.45, 0.5, 0.0], VIGNETTE_PARAMS),
+            make_effect("Film Grain", include_str!("shader_post_grain.wgsl"), false, [0.06, 0.0, 0.0, 0.0], GRAIN_PARAMS),
+        ];
+
+        Self {
+            enabled: true,
+            effects,
+            selected: (0, 0),
+            targets,
+            width,
+            height,
+        }
+    }
+
+    /// Hay al menos un efecto que aplicar
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.effects.iter().any(|e| e.enabled)
+    }
+
+    /// Textura donde debe escribir el tone mapping cuando la cadena está activa
+    pub fn input_view(&self) -> &wgpu::TextureView {
+        &self.targets[0]
+    }
+
+    pub fn process_action(&mut self, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+
+        let index = match action {
+            Action::PostEffect1 => 0,
+            Action::PostEffect2 => 1,
+            Action::PostEffect3 => 2,
+            Action::PostEffect4 => 3,
+            Action::TogglePost => {
+                self.enabled = !self.enabled;
+                println!("Post-procesado: {}", if self.enabled { "ACTIVADO" } else { "DESACTIVADO" });
+                return;
+            }
+            Action::PostNextParam => {
+                // Recorre todos los parámetros de todos los efectos
+                let (mut effect, mut param) = self.selected;
+                param += 1;
+                if param >= self.effects[effect].param_info.len() {
+                    effect = (effect + 1) % self.effects.len();
+                    param = 0;
+                }
+                self.selected = (effect, param);
+                self.print_selected();
+                return;
+            }
+            Action::PostParamUp | Action::PostParamDown => {
+                let (effect, param) = self.selected;
+                let info = &self.effects[effect].param_info[param];
+                let step = if action == Action::PostParamUp { info.step } else { -info.step };
+                let value = &mut self.effects[effect].params[param];
+                *value = (*value + step).clamp(info.range.0, info.range.1);
+                self.print_selected();
+                return;
+            }
+            _ => return,
+        };
+
+        if let Some(effect) = self.effects.get_mut(index) {
+            effect.enabled = !effect.enabled;
+            println!("{}: {}", effect.name, if effect.enabled { "ACTIVADO" } else { "DESACTIVADO" });
+        }
+    }
+
+    fn print_selected(&self) {
+        let (effect, param) = self.selected;
+        let effect = &self.effects[effect];
+        println!(
+            "{} · {}: {:.2}{}",
+            effect.name,
+            effect.param_info[param].name,
+            effect.params[param],
+            if effect.enabled { "" } else { " (efecto desactivado)" }
+        );
+    }
+
+    /// Aplica los efectos activos leyendo de `input_view()` y escribe en `output`
+    pub fn run(&self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, output: &wgpu::TextureView, time: f32) {
+        let active: Vec<&PostEffect> = self.effects.iter().filter(|e| e.enabled).collect();
+        let mut current = 0;
+
+        for (i, effect) in active.iter().enumerate() {
+            let uniforms = PostUniforms {
+                params: effect.params,
+                resolution: [self.width as f32, self.height as f32],
+                time,
+                _pad: 0.0,
+            };
+            queue.write_buffer(&effect.uniform_buf, 0, bytemuck::bytes_of(&uniforms));
+
+            let last = i + 1 == active.len();
+            let target = if last { output } else { &self.targets[1 - current] };
+
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(effect.name),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&effect.pipeline);
+            pass.set_bind_group(0, &effect.bind_groups[current], &[]);
+            pass.draw(0..3, 0..1);
+
+            current = 1 - current;
+        }
+    }
+}
@@ -1,3 +1,6 @@
+// Texturas procedurales en CPU (actualmente sin usar; los colores se generan en el shader)
+#![allow(dead_code)]
+
 use rand::Rng;
 
 pub fn create_procedural_texture(device: &wgpu::Device, queue: &wgpu::Queue, kind: &str) -> (wgpu::TextureView, wgpu::Sampler) {
@@ -75,7 +78,7 @@ fn rock_color(u: f32, v: f32, rng: &mut impl Rng) -> [u8; 4] {
     let b = (60.0 + 40.0*(v+n)) as u8;
     [r,g,b,255]
 }
-fn gas_color(u: f32, v: f32) -> [u8; 4] {
+fn gas_color(_u: f32, v: f32) -> [u8; 4] {
     let w = ((v*20.0).sin()*0.5+0.5)*0.8+0.2;
     [(100.0+100.0*w) as u8, (150.0+80.0*w) as u8, (200.0-60.0*w) as u8, 255]
 }
@@ -83,7 +86,7 @@ fn ice_color(u: f32, v: f32) -> [u8; 4] {
     let w = ((u*10.0).cos()*(v*10.0).sin())*0.5+0.5;
     [(150.0*w+80.0) as u8,(200.0*w+55.0) as u8,(255.0*w) as u8,255]
 }
-fn ring_color(u: f32, v: f32) -> [u8; 4] {
+fn ring_color(_u: f32, v: f32) -> [u8; 4] {
     let d = ((v-0.5)*2.0).abs();
     let fade = (1.0 - d*d).max(0.0);
     [180,180,160,(200.0*fade) as u8]
@@ -0,0 +1,419 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use glam::{Vec3, Vec4};
+use wgpu::util::DeviceExt;
+use crate::input::Action;
+
+/// Paso máximo (s) de las simulaciones con integración (N-cuerpos, rocas):
+/// un frame lento no debe lanzar los cuerpos fuera del sistema
+pub const MAX_SIM_STEP: f32 = 0.1;
+
+// Estado del map_async de la lectura de posiciones
+const MAP_PENDING: u8 = 0;
+const MAP_OK: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// Elementos keplerianos de una órbita alrededor del sol. Los ángulos van en
+/// radianes; el plano de referencia es el XZ de la escena, con el mismo
+/// sentido de giro que las órbitas circulares de `orbit.rs`.
+#[derive(Clone, Copy)]
+pub struct KeplerElements {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32, // 0..1 (solo órbitas cerradas)
+    pub inclination: f32,
+    pub node: f32,         // longitud del nodo ascendente
+    pub periapsis: f32,    // argumento del periapsis
+    pub mean_anomaly: f32, // en t = 0
+}
+
+impl KeplerElements {
+    /// Base del plano de la órbita: dirección del periapsis y su perpendicular
+    /// en el sentido del movimiento
+    pub fn basis(&self) -> (Vec3, Vec3) {
+        let (so, co) = self.node.sin_cos();
+        let (sw, cw) = self.periapsis.sin_cos();
+        let (si, ci) = self.inclination.sin_cos();
+        // Fórmulas clásicas con Z arriba, pasadas a Y arriba (x, z, y)
+        let p = Vec3::new(co * cw - so * sw * ci, sw * si, so * cw + co * sw * ci);
+        let q = Vec3::new(-co * sw - so * cw * ci, cw * si, -so * sw + co * cw * ci);
+        (p, q)
+    }
+
+    /// Movimiento medio (rad/s) con el parámetro gravitatorio `mu` = G·M
+    pub fn mean_motion(&self, mu: f32) -> f32 {
+        (mu / self.semi_major_axis.powi(3)).sqrt()
+    }
+
+    /// Posición y velocidad en el instante `time`
+    pub fn state_vectors(&self, mu: f32, time: f32) -> (Vec3, Vec3) {
+        let (a, e) = (self.semi_major_axis, self.eccentricity);
+        let m = (self.mean_anomaly + self.mean_motion(mu) * time).rem_euclid(std::f32::consts::TAU);
+        // Partir de π converge siempre, también en órbitas muy excéntricas
+        let mut ecc = if e > 0.8 { std::f32::consts::PI } else { m + e * m.sin() };
+        for _ in 0..8 {
+            ecc -= (ecc - e * ecc.sin() - m) / (1.0 - e * ecc.cos());
+        }
+
+        let (p, q) = self.basis();
+        let b = (1.0 - e * e).sqrt();
+        let pos = p * (a * (ecc.cos() - e)) + q * (a * b * ecc.sin());
+        let r = a * (1.0 - e * ecc.cos());
+        let vel = (p * -ecc.sin() + q * (b * ecc.cos())) * ((mu * a).sqrt() / r);
+        (pos, vel)
+    }
+}
+
+/// Cómo avanzan los cuerpos de un propagador
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Propagation {
+    Kepler, // órbitas analíticas, sin interacción entre cuerpos
+    NBody,  // gravedad mutua integrada con leapfrog
+}
+
+/// Cuerpo pequeño (roca, cometa, resto) propagado en la GPU
+#[derive(Clone, Copy)]
+pub struct PropagatedBody {
+    pub elements: KeplerElements,
+    pub mass: f32, // G·m; 0 = no atrae a los demás
+    pub size: f32,
+    pub spin_axis: Vec3,
+    pub spin_speed: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PropagatorParams {
+    time: f32,
+    dt: f32,
+    mu: f32,
+    softening: f32,
+    count: u32,
+    _pad: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BodyState {
+    p: [f32; 4],         // w = semieje mayor
+    q: [f32; 4],         // w = excentricidad
+    motion: [f32; 4],    // movimiento medio, anomalía media, giro, masa
+    spin_axis: [f32; 4], // w = tamaño
+    velocity: [f32; 4],
+}
+
+/// Transformación de un cuerpo, escrita por el compute shader y usada
+/// directamente como instancia al dibujar
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct BodyInstance {
+    pub position: [f32; 4], // w = tamaño
+    pub rotation: [f32; 4], // cuaternión
+}
+
+impl BodyInstance {
+    /// Layout de instancias (locations 4 y 5) para los pipelines que dibujan
+    /// el resultado del propagador
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<BodyInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                4 => Float32x4,  // position + tamaño
+                5 => Float32x4   // rotation
+            ])),
+        }
+    }
+}
+
+/// Propaga un buffer de cuerpos en la GPU cada frame, con órbitas de Kepler
+/// o gravedad de N cuerpos. Las posiciones se quedan en la GPU; la CPU solo
+/// las lee (con uno o dos frames de retraso) cuando las pide para
+/// colisiones o selección.
+pub struct GpuPropagator {
+    pub mode: Propagation,
+    pub softening: f32,
+    pub substeps: u32,
+    mu: f32,
+    count: u32,
+    params_buf: wgpu::Buffer,
+    instance_buf: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    kepler_pipeline: wgpu::ComputePipeline,
+    kick_pipeline: wgpu::ComputePipeline,
+    drift_pipeline: wgpu::ComputePipeline,
+    readback_buf: wgpu::Buffer,
+    copy_pending: bool, // se copió al buffer de lectura en este frame
+    mapping: bool,      // hay un map_async en curso o el buffer sigue mapeado
+    map_state: Arc<AtomicU8>,
+    positions: Vec<Vec4>, // última lectura (w = tamaño)
+}
+
+impl GpuPropagator {
+    pub fn new(device: &wgpu::Device, mu: f32, mode: Propagation, bodies: &[PropagatedBody]) -> Self {
+        // Al menos un elemento para que los bindings de storage sean válidos
+        let mut states = Vec::with_capacity(bodies.len().max(1));
+        let mut instances = Vec::with_capacity(bodies.len().max(1));
+        for b in bodies {
+            let (p, q) = b.elements.basis();
+            let (pos, vel) = b.elements.state_vectors(mu, 0.0);
+            states.push(BodyState {
+                p: p.extend(b.elements.semi_major_axis).to_array(),
+                q: q.extend(b.elements.eccentricity).to_array(),
+                motion: [b.elements.mean_motion(mu), b.elements.mean_anomaly, b.spin_speed, b.mass],
+                spin_axis: b.spin_axis.extend(b.size).to_array(),
+                velocity: vel.extend(0.0).to_array(),
+            });
+            instances.push(BodyInstance {
+                position: pos.extend(b.size).to_array(),
+                rotation: [0.0, 0.0, 0.0, 1.0],
+            });
+        }
+        if bodies.is_empty() {
+            states.push(bytemuck::Zeroable::zeroed());
+            instances.push(bytemuck::Zeroable::zeroed());
+        }
+
+        let params_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Propagator Params"),
+            size: std::mem::size_of::<PropagatorParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let state_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Propagator States"),
+            contents: bytemuck::cast_slice(&states),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let instance_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Propagator Instances"),
+            contents: bytemuck::cast_slice(&instances),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC,
+        });
+        let readback_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Propagator Readback"),
+            size: instance_buf.size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Propagator Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1),
+                storage(2),
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Propagator BG"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params_buf.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: state_buf.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: instance_buf.as_entire_binding() },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shader_propagator.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Propagator Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point,
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+        };
+
+        Self {
+            mode,
+            softening: 0.5,
+            substeps: 4,
+            mu,
+            count: bodies.len() as u32,
+            params_buf,
+            kepler_pipeline: pipeline("cs_kepler"),
+            kick_pipeline: pipeline("cs_kick"),
+            drift_pipeline: pipeline("cs_drift"),
+            instance_buf,
+            bind_group,
+            readback_buf,
+            copy_pending: false,
+            mapping: false,
+            map_state: Arc::new(AtomicU8::new(MAP_PENDING)),
+            positions: Vec::new(),
+        }
+    }
+
+    pub fn process_action(&mut self, action: Action, pressed: bool) {
+        if pressed && action == Action::TogglePropagation {
+            self.mode = match self.mode {
+                Propagation::Kepler => Propagation::NBody,
+                Propagation::NBody => Propagation::Kepler,
+            };
+            println!("Propagación: {:?}", self.mode);
+        }
+    }
+
+    /// Buffer de instancias con el resultado; se enlaza como vertex buffer
+    pub fn instances(&self) -> &wgpu::Buffer {
+        &self.instance_buf
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Avanza hasta `time`. En modo Kepler se evalúa la órbita en ese instante;
+    /// en N-cuerpos se integra `dt` en `substeps` pasos leapfrog.
+    pub fn update(&mut self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, time: f32, dt: f32) {
+        if self.count == 0 {
+            return;
+        }
+        let step = dt.min(MAX_SIM_STEP) / self.substeps.max(1) as f32;
+        let params = PropagatorParams {
+            time,
+            dt: step,
+            mu: self.mu,
+            softening: self.softening,
+            count: self.count,
+            _pad: [0; 3],
+        };
+        queue.write_buffer(&self.params_buf, 0, bytemuck::bytes_of(&params));
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Propagator Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        let groups = self.count.div_ceil(64);
+        match self.mode {
+            Propagation::Kepler => {
+                pass.set_pipeline(&self.kepler_pipeline);
+                pass.dispatch_workgroups(groups, 1, 1);
+            }
+            Propagation::NBody => {
+                for _ in 0..self.substeps.max(1) {
+                    for pipeline in [&self.kick_pipeline, &self.drift_pipeline, &self.kick_pipeline] {
+                        pass.set_pipeline(pipeline);
+                        pass.dispatch_workgroups(groups, 1, 1);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copia las instancias al buffer de lectura, salvo que la lectura
+    /// anterior siga pendiente. Llamar solo cuando la CPU las necesite.
+    pub fn copy_readback(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.mapping || self.count == 0 {
+            return;
+        }
+        encoder.copy_buffer_to_buffer(&self.instance_buf, 0, &self.readback_buf, 0, self.readback_buf.size());
+        self.copy_pending = true;
+    }
+
+    /// Pide el mapeo del buffer de lectura; llamar después de `queue.submit`
+    pub fn request_readback(&mut self) {
+        if !self.copy_pending {
+            return;
+        }
+        self.copy_pending = false;
+        self.mapping = true;
+        let state = self.map_state.clone();
+        state.store(MAP_PENDING, Ordering::Release);
+        self.readback_buf.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            state.store(if result.is_ok() { MAP_OK } else { MAP_FAILED }, Ordering::Release);
+        });
+    }
+
+    /// Recoge la lectura si ya llegó y devuelve la última disponible
+    /// (posición y tamaño de cada cuerpo)
+    pub fn positions(&mut self, device: &wgpu::Device) -> &[Vec4] {
+        device.poll(wgpu::Maintain::Poll);
+        match self.map_state.load(Ordering::Acquire) {
+            MAP_OK if self.mapping => {
+                {
+                    let data = self.readback_buf.slice(..).get_mapped_range();
+                    let instances: &[BodyInstance] = bytemuck::cast_slice(&data);
+                    self.positions = instances.iter().map(|i| Vec4::from(i.position)).collect();
+                }
+                self.readback_buf.unmap();
+                self.mapping = false;
+            }
+            // El buffer no llegó a mapearse: se copia de nuevo en el
+            // siguiente frame y mientras se usa la lectura anterior
+            MAP_FAILED if self.mapping => {
+                self.mapping = false;
+                self.copy_pending = false;
+            }
+            _ => {}
+        }
+        &self.positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orbit(a: f32, e: f32) -> KeplerElements {
+        KeplerElements { semi_major_axis: a, eccentricity: e, inclination: 0.4, node: 1.1, periapsis: 2.3, mean_anomaly: 0.7 }
+    }
+
+    #[test]
+    fn circular_radius_and_speed() {
+        let (mu, a) = (243.0, 50.0);
+        let el = orbit(a, 0.0);
+        for i in 0..20 {
+            let (pos, vel) = el.state_vectors(mu, i as f32 * 3.7);
+            assert!((pos.length() - a).abs() < 1e-3 * a);
+            assert!((vel.length() - (mu / a).sqrt()).abs() < 1e-3);
+            assert!(pos.normalize().dot(vel.normalize()).abs() < 1e-3);
+        }
+        // Tras un periodo vuelve al mismo sitio
+        let period = std::f32::consts::TAU / el.mean_motion(mu);
+        let (start, _) = el.state_vectors(mu, 0.0);
+        assert!(el.state_vectors(mu, period).0.distance(start) < 1e-2);
+    }
+
+    #[test]
+    fn eccentric_vis_viva() {
+        let (mu, a) = (243.0, 20.0);
+        for e in [0.3, 0.9] {
+            let el = orbit(a, e);
+            for i in 0..20 {
+                let (pos, vel) = el.state_vectors(mu, i as f32 * 1.3);
+                let r = pos.length();
+                assert!(r > a * (1.0 - e) * 0.999 && r < a * (1.0 + e) * 1.001);
+                let expected = mu * (2.0 / r - 1.0 / a);
+                assert!((vel.length_squared() - expected).abs() < 1e-3 * expected, "e = {}", e);
+            }
+        }
+    }
+}
@@ -1,5 +1,19 @@
+use std::borrow::Cow;
 use wgpu::util::DeviceExt;
 use crate::mesh::Vertex;
+use crate::orbit::{GpuOrbits, OrbitPoint};
+use crate::trail::{GpuTrails, TrailVertex};
+use crate::hdr::{Hdr, HDR_FORMAT};
+use crate::postprocess::PostChain;
+use crate::sun::SunEffects;
+use crate::particles::{billboard_shader, ParticleSystem};
+use crate::shadow::ShadowMap;
+use crate::asteroids::GpuAsteroids;
+use crate::comets::GpuComets;
+use crate::atmosphere::GpuAtmospheres;
+use crate::clouds::{CloudInstance, GpuClouds};
+use crate::surface::GpuSurfaces;
+use crate::terrain::{ChunkInstance, GpuTerrain};
 use glam::Mat4;
 
 #[repr(C)]
@@ -8,8 +22,8 @@ pub struct Globals {
     pub view_proj: [[f32; 4]; 4],
     pub time: f32,
     pub _pad0: [f32; 3],
-    pub _pad1: [f32; 4],
-    pub _pad2: [f32; 4],
+    pub viewport: [f32; 4], // (ancho, alto, 1/ancho, 1/alto)
+    pub camera_pos: [f32; 4], // w sin usar
     pub _pad3: [f32; 4],
 }
 
@@ -18,22 +32,40 @@ pub struct Renderer {
     pub globals_bg: wgpu::BindGroup,
     pub pipeline: wgpu::RenderPipeline,
     pub orbit_pipeline: wgpu::RenderPipeline,
+    pub orbit_bg_layout: wgpu::BindGroupLayout,
+    pub trail_pipeline: wgpu::RenderPipeline,
+    pub atmosphere_pipeline: wgpu::RenderPipeline,
+    pub atmosphere_bg_layout: wgpu::BindGroupLayout,
+    pub cloud_pipeline: wgpu::RenderPipeline,
+    pub asteroid_pipeline: wgpu::RenderPipeline,
+    pub comet_pipeline: wgpu::RenderPipeline,
+    pub terrain_pipeline: wgpu::RenderPipeline,
+    pub terrain_bg_layout: wgpu::BindGroupLayout,
     pub skybox_pipeline: wgpu::RenderPipeline,
-    pub dummy_texture: wgpu::TextureView,
-    pub dummy_sampler: wgpu::Sampler,
     pub depth_texture: wgpu::TextureView,
-    pub skybox_vb: wgpu::Buffer,
-    pub skybox_ib: wgpu::Buffer,
+    msaa_texture: Option<wgpu::TextureView>, // None si sample_count == 1
+    pub hdr: Hdr,
+    pub post: PostChain,
+    pub sun: SunEffects,
+    pub particles: ParticleSystem,
+    pub shadows: ShadowMap,
+    pub surfaces: GpuSurfaces,
 }
 
 impl Renderer {
-    pub async fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+    pub async fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> Self {
         let globals_init = Globals {
             view_proj: Mat4::IDENTITY.to_cols_array_2d(),
             time: 0.0,
             _pad0: [0.0; 3],
-            _pad1: [0.0; 4],
-            _pad2: [0.0; 4],
+            viewport: [width as f32, height as f32, 1.0 / width as f32, 1.0 / height as f32],
+            camera_pos: [0.0; 4],
             _pad3: [0.0; 4],
         };
 
@@ -51,8 +83,18 @@ impl Renderer {
             ..Default::default()
         });
 
-        // Crear depth texture
-        let depth_texture = Self::create_depth_texture(device, width, height);
+        // Crear depth texture y, con MSAA, el color multisample que se resuelve al final
+        let depth_texture = Self::create_depth_texture(device, width, height, sample_count);
+        let msaa_texture = (sample_count > 1)
+            .then(|| Self::create_msaa_texture(device, width, height, sample_count));
+
+        // La escena se dibuja en HDR y se lleva a `format` en el tone mapping
+        let hdr = Hdr::new(device, format, width, height);
+        let post = PostChain::new(device, format, width, height);
+        let multisample = wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        };
 
         let globals_bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Globals Layout"),
@@ -105,9 +147,24 @@ impl Renderer {
             label: Some("Globals BG"),
         });
 
-        let shader = device.create_shader_module(wgpu::include_wgsl!("shader_textured.wgsl"));
+        let sun = SunEffects::new(device, &globals_bg_layout, sample_count);
+        let particles = ParticleSystem::new(device, &globals_bg_layout, sample_count);
+        let shadows = ShadowMap::new(device);
+        let surfaces = GpuSurfaces::new(device);
+
+        // Los modelos usan la biblioteca de ruido para su superficie
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shader_textured.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(format!(
+                "{}\n{}",
+                include_str!("shader_noise.wgsl"),
+                include_str!("shader_textured.wgsl")
+            ))),
+        });
         let orbit_shader = device.create_shader_module(wgpu::include_wgsl!("shader_orbit.wgsl"));
         let skybox_shader = device.create_shader_module(wgpu::include_wgsl!("shader_skybox.wgsl"));
+        let atmosphere_shader = device.create_shader_module(wgpu::include_wgsl!("shader_atmosphere.wgsl"));
+        let comet_shader = device.create_shader_module(billboard_shader("shader_comet.wgsl", include_str!("shader_comet.wgsl")));
 
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Pipeline Layout"),
@@ -115,9 +172,97 @@ impl Renderer {
             push_constant_ranges: &[],
         });
 
+        // Los modelos además muestrean el cubo de sombras y leen su superficie
+        let mesh_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mesh Pipeline Layout"),
+            bind_group_layouts: &[&globals_bg_layout, &shadows.layout, &surfaces.layout],
+            push_constant_ranges: &[],
+        });
+
+        // Terreno: centro, radio y amplitud del cuerpo más su heightmap
+        let terrain_bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Terrain Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let terrain_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Terrain Pipeline Layout"),
+            bind_group_layouts: &[&globals_bg_layout, &shadows.layout, &surfaces.layout, &terrain_bg_layout],
+            push_constant_ranges: &[],
+        });
+
+        // Parámetros por órbita (color, centro, posición del cuerpo)
+        let orbit_bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Orbit Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let orbit_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Orbit Pipeline Layout"),
+            bind_group_layouts: &[&globals_bg_layout, &orbit_bg_layout],
+            push_constant_ranges: &[],
+        });
+
+        // Parámetros por atmósfera (centro, radios, coeficientes, sol)
+        let atmosphere_bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Atmosphere Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let atmosphere_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Atmosphere Pipeline Layout"),
+            bind_group_layouts: &[&globals_bg_layout, &atmosphere_bg_layout],
+            push_constant_ranges: &[],
+        });
+
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Main Pipeline"),
-            layout: Some(&layout),
+            layout: Some(&mesh_layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
@@ -128,7 +273,7 @@ impl Renderer {
                 module: &shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format,
+                    format: HDR_FORMAT,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -142,34 +287,245 @@ impl Renderer {
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
-            multisample: wgpu::MultisampleState::default(),
+            multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        // Mismo shader y fragment que los modelos, con el relieve en el vertex
+        let terrain_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Terrain Pipeline"),
+            layout: Some(&terrain_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_terrain",
+                buffers: &ChunkInstance::buffer_layouts(),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample,
             multiview: None,
             cache: None,
         });
 
         let orbit_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Orbit Pipeline"),
+            layout: Some(&orbit_layout),
+            vertex: wgpu::VertexState {
+                module: &orbit_shader,
+                entry_point: "vs_main",
+                buffers: &OrbitPoint::segment_layouts(),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &orbit_shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(), // quads por segmento, sin culling
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        let trail_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Trail Pipeline"),
             layout: Some(&layout),
             vertex: wgpu::VertexState {
                 module: &orbit_shader,
+                entry_point: "vs_trail",
+                buffers: &[TrailVertex::layout()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &orbit_shader,
+                entry_point: "fs_trail",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        // Atmósferas: el shader ya devuelve el color premultiplicado por su opacidad
+        let atmosphere_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Atmosphere Pipeline"),
+            layout: Some(&atmosphere_layout),
+            vertex: wgpu::VertexState {
+                module: &atmosphere_shader,
                 entry_point: "vs_main",
                 buffers: &[Vertex::layout()],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &orbit_shader,
+                module: &atmosphere_shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format,
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            // Sin culling: el shader elige la cara frontal o la trasera según
+            // la cámara esté fuera o dentro de la capa
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        // Asteroides: el fragment de los modelos con la transformación por instancia
+        let asteroid_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Asteroid Pipeline"),
+            layout: Some(&mesh_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_asteroid",
+                buffers: &GpuAsteroids::buffer_layouts(),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             }),
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::LineStrip,
+                cull_mode: Some(wgpu::Face::Back),
                 ..Default::default()
             },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        // Nubes: mismo shader que los modelos, premultiplicadas como las atmósferas
+        let cloud_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Cloud Pipeline"),
+            layout: Some(&mesh_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_clouds",
+                buffers: &CloudInstance::buffer_layouts(),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_clouds",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            // Sin culling: el shader elige la cara según la cámara esté
+            // fuera o por debajo de la capa
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        // Colas de cometas: partículas aditivas que no tapan nada
+        let comet_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Comet Tail Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &comet_shader,
+                entry_point: "vs_tail",
+                buffers: &GpuComets::tail_layouts(),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &comet_shader,
+                entry_point: "fs_tail",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::OVER,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: wgpu::TextureFormat::Depth32Float,
                 depth_write_enabled: false,
@@ -177,7 +533,7 @@ impl Renderer {
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
-            multisample: wgpu::MultisampleState::default(),
+            multisample,
             multiview: None,
             cache: None,
         });
@@ -196,7 +552,7 @@ impl Renderer {
                 module: &skybox_shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format,
+                    format: HDR_FORMAT,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -210,40 +566,128 @@ impl Renderer {
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
-            multisample: wgpu::MultisampleState::default(),
+            multisample,
             multiview: None,
             cache: None,
         });
 
-        // Crear buffers dummy para skybox
-        let skybox_vb = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Skybox VB"),
-            size: 0,
-            usage: wgpu::BufferUsages::VERTEX,
-            mapped_at_creation: false,
-        });
-
-        let skybox_ib = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Skybox IB"),
-            size: 0,
-            usage: wgpu::BufferUsages::INDEX,
-            mapped_at_creation: false,
-        });
-
         Self {
             globals_buf,
             globals_bg,
             pipeline,
             orbit_pipeline,
+            orbit_bg_layout,
+            trail_pipeline,
+            atmosphere_pipeline,
+            atmosphere_bg_layout,
+            cloud_pipeline,
+            asteroid_pipeline,
+            comet_pipeline,
+            terrain_pipeline,
+            terrain_bg_layout,
             skybox_pipeline,
-            dummy_texture,
-            dummy_sampler,
             depth_texture,
-            skybox_vb,
-            skybox_ib,
+            msaa_texture,
+            hdr,
+            post,
+            sun,
+            particles,
+            shadows,
+            surfaces,
         }
     }
 
+    /// Mayor número de muestras soportado que no supere `requested`
+    /// (1, 2, 4 u 8) para el formato HDR y el de profundidad.
+    pub fn supported_sample_count(adapter: &wgpu::Adapter, requested: u32) -> u32 {
+        let color = adapter.get_texture_format_features(HDR_FORMAT).flags;
+        let depth = adapter
+            .get_texture_format_features(wgpu::TextureFormat::Depth32Float)
+            .flags;
+        // Sin esta feature solo se garantizan 1 y 4 muestras
+        let adapter_specific = adapter
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+
+        [8, 4, 2, 1]
+            .into_iter()
+            .filter(|&n| n <= requested)
+            .find(|&n| {
+                n == 1
+                    || ((adapter_specific || n == 4)
+                        && color.sample_count_supported(n)
+                        && depth.sample_count_supported(n))
+            })
+            .unwrap_or(1)
+    }
+
+    /// Attachment de color del pase principal: la escena se dibuja en el
+    /// target HDR (con MSAA, en la textura multisample que se resuelve sobre él).
+    pub fn main_color_attachment(&self, clear: wgpu::Color) -> wgpu::RenderPassColorAttachment<'_> {
+        let view = &self.hdr.view;
+        match &self.msaa_texture {
+            Some(msaa) => wgpu::RenderPassColorAttachment {
+                view: msaa,
+                resolve_target: Some(view),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear),
+                    store: wgpu::StoreOp::Discard,
+                },
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear),
+                    store: wgpu::StoreOp::Store,
+                },
+            },
+        }
+    }
+
+    /// Destello del sol, bloom, tone mapping y cadena de efectos de la escena
+    /// HDR sobre `output` (el swapchain)
+    pub fn post_process(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+        time: f32,
+    ) {
+        // El destello se suma en HDR antes del bloom
+        self.sun.draw_flare(encoder, &self.globals_bg, &self.hdr.view);
+
+        if self.post.is_active() {
+            self.hdr.resolve(queue, encoder, self.post.input_view());
+            self.post.run(queue, encoder, output, time);
+        } else {
+            self.hdr.resolve(queue, encoder, output);
+        }
+    }
+
+    fn create_msaa_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Color Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
     fn create_dummy_texture(device: &wgpu::Device) -> wgpu::TextureView {
         let size = wgpu::Extent3d {
             width: 1,
@@ -263,7 +707,7 @@ impl Renderer {
         texture.create_view(&wgpu::TextureViewDescriptor::default())
     }
 
-    fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::TextureView {
+    fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32, sample_count: u32) -> wgpu::TextureView {
         let size = wgpu::Extent3d {
             width,
             height,
@@ -273,7 +717,7 @@ impl Renderer {
             label: Some("Depth Texture"),
             size,
             mip_level_count: 1,
-            sample_count: 1,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Depth32Float,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -291,23 +735,23 @@ impl Renderer {
     ) {
         pass.set_pipeline(&self.pipeline);
         pass.set_bind_group(0, &self.globals_bg, &[]);
+        pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        pass.set_bind_group(2, &self.surfaces.bind_group, &[]);
         pass.set_vertex_buffer(0, vb.slice(..));
         pass.set_index_buffer(ib.slice(..), wgpu::IndexFormat::Uint32);
         pass.draw_indexed(0..ic, 0, 0..1);
     }
 
-    pub fn draw_player_ship<'a>(
+    pub fn draw_terrain<'a>(
         &'a self,
         pass: &mut wgpu::RenderPass<'a>,
-        vb: &'a wgpu::Buffer,
-        ib: &'a wgpu::Buffer,
-        ic: u32,
+        terrain: &'a GpuTerrain,
     ) {
-        pass.set_pipeline(&self.pipeline);
+        pass.set_pipeline(&self.terrain_pipeline);
         pass.set_bind_group(0, &self.globals_bg, &[]);
-        pass.set_vertex_buffer(0, vb.slice(..));
-        pass.set_index_buffer(ib.slice(..), wgpu::IndexFormat::Uint32);
-        pass.draw_indexed(0..ic, 0, 0..1);
+        pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        pass.set_bind_group(2, &self.surfaces.bind_group, &[]);
+        terrain.draw(pass);
     }
 
     pub fn draw_skybox<'a>(
@@ -319,34 +763,93 @@ impl Renderer {
         pass.draw(0..6, 0..1); // Dibuja 6 vértices para un fullscreen quad
     }
 
+    pub fn draw_corona<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        self.sun.draw_corona(pass, &self.globals_bg);
+    }
+
+    pub fn draw_sun_probes<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        self.sun.draw_probes(pass, &self.globals_bg);
+    }
+
     pub fn draw_orbits<'a>(
         &'a self,
         pass: &mut wgpu::RenderPass<'a>,
-        device: &wgpu::Device,
-        orbits: &Vec<Vec<glam::Vec3>>,
+        orbits: &'a GpuOrbits,
     ) {
         pass.set_pipeline(&self.orbit_pipeline);
         pass.set_bind_group(0, &self.globals_bg, &[]);
+        orbits.draw(pass);
+    }
 
-        for orbit in orbits {
-            let verts: Vec<Vertex> = orbit
-                .iter()
-                .map(|p| Vertex {
-                    pos: p.to_array(),
-                    normal: [0.0; 3],
-                    uv: [0.0; 2],
-                    planet_id: 0,
-                })
-                .collect();
-
-            let vb = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Orbit VB"),
-                contents: bytemuck::cast_slice(&verts),
-                usage: wgpu::BufferUsages::VERTEX,
-            });
-
-            pass.set_vertex_buffer(0, vb.slice(..));
-            pass.draw(0..verts.len() as u32, 0..1);
-        }
+    pub fn draw_atmospheres<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        atmospheres: &'a GpuAtmospheres,
+    ) {
+        pass.set_pipeline(&self.atmosphere_pipeline);
+        pass.set_bind_group(0, &self.globals_bg, &[]);
+        atmospheres.draw(pass);
+    }
+
+    pub fn draw_asteroids<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        asteroids: &'a GpuAsteroids,
+    ) {
+        pass.set_pipeline(&self.asteroid_pipeline);
+        pass.set_bind_group(0, &self.globals_bg, &[]);
+        pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        pass.set_bind_group(2, &self.surfaces.bind_group, &[]);
+        asteroids.draw(pass);
+    }
+
+    /// Núcleos de los cometas: rocas con el mismo pipeline que el cinturón
+    pub fn draw_comet_nuclei<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        comets: &'a GpuComets,
+    ) {
+        pass.set_pipeline(&self.asteroid_pipeline);
+        pass.set_bind_group(0, &self.globals_bg, &[]);
+        pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        pass.set_bind_group(2, &self.surfaces.bind_group, &[]);
+        comets.draw_nuclei(pass);
+    }
+
+    pub fn draw_comet_tails<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        comets: &'a GpuComets,
+    ) {
+        pass.set_pipeline(&self.comet_pipeline);
+        pass.set_bind_group(0, &self.globals_bg, &[]);
+        comets.draw_tails(pass);
     }
-}
\ No newline at end of file
+
+    /// Partículas aditivas; van después de todo lo opaco
+    pub fn draw_particles<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        self.particles.draw(pass, &self.globals_bg);
+    }
+
+    pub fn draw_clouds<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        clouds: &'a GpuClouds,
+    ) {
+        pass.set_pipeline(&self.cloud_pipeline);
+        pass.set_bind_group(0, &self.globals_bg, &[]);
+        pass.set_bind_group(1, &self.shadows.bind_group, &[]);
+        pass.set_bind_group(2, &self.surfaces.bind_group, &[]);
+        clouds.draw(pass);
+    }
+
+    pub fn draw_trails<'a>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'a>,
+        trails: &'a GpuTrails,
+    ) {
+        pass.set_pipeline(&self.trail_pipeline);
+        pass.set_bind_group(0, &self.globals_bg, &[]);
+        trails.draw(pass);
+    }
+}
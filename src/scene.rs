@@ -1,7 +1,21 @@
-use crate::mesh::{load_obj, Vertex};
+use crate::mesh::{generate_shape, load_obj, Shape, Vertex};
+use crate::orbit::{Orbit, OrbitStyle};
+use crate::trail::Trail;
+use crate::asteroids::{AsteroidBelt, ASTEROID_SURFACE};
+use crate::comets::Comet;
+use crate::propagator::KeplerElements;
+use crate::atmosphere::Atmosphere;
+use crate::surface::{Surface, SurfaceKind};
+use crate::terrain::{HeightSource, Terrain};
+use crate::nbody::{Collision, NBodyScene, NBodySim};
+use crate::input::Action;
 use wgpu::util::DeviceExt;
 use glam::Vec3;
 
+/// G·M del sol para los cuerpos con órbita kepleriana (cinturón y cometas);
+/// también es la masa del sol en src/scenes/sistema.toml si no se da otra
+pub const SUN_MU: f32 = 243.0;
+
 pub struct GpuModel {
     pub vb: wgpu::Buffer,
     pub ib: wgpu::Buffer,
@@ -10,9 +24,19 @@ pub struct GpuModel {
 
 pub struct Scene {
     pub models: Vec<GpuModel>,
-    pub orbits: Vec<Vec<Vec3>>,
+    pub orbits: Vec<Orbit>,
+    pub trails: Vec<Trail>,
+    pub atmospheres: Vec<Atmosphere>,
+    pub surfaces: Vec<Surface>,
+    pub terrains: Vec<Terrain>,
+    pub belt: AsteroidBelt,
+    pub comets: Vec<Comet>,
     pub planet_positions: Vec<(Vec3, f32)>, // (posición, radio) de cada planeta
-    pub ship_rot: (f32, f32), // Rotación de la nave en órbita (yaw, pitch)
+    pub ship_rot: (f32, f32, f32), // Rotación de la nave en órbita (yaw, pitch, alabeo)
+
+    // Modo N-cuerpos: escena leída del archivo y simulación en curso
+    nbody_scene: Option<NBodyScene>,
+    nbody: Option<NBodySim>,
 
     // 🔥 movimiento dinámico
     original_vertices: Vec<Vec<Vertex>>,
@@ -21,29 +45,51 @@ pub struct Scene {
 
 impl Scene {
     pub fn load_models(device: &wgpu::Device) -> Self {
-        let paths = [
+        // (posición, radio) de cada cuerpo; el radio también da el tamaño de
+        // las mallas generadas
+        let planet_positions = vec![
+            (Vec3::ZERO, 5.0),           // Sol
+            (Vec3::ZERO, 1.5),           // Planeta 1
+            (Vec3::ZERO, 1.0),           // Nave
+            (Vec3::ZERO, 2.0),           // Planeta 2
+            (Vec3::ZERO, 2.5),           // Planeta 3
+            (Vec3::ZERO, 1.8),           // Planeta huevo
+            (Vec3::ZERO, 0.8),           // Luna
+        ];
+
+        // (OBJ opcional, forma generada si no hay OBJ o no se puede cargar)
+        let bodies = [
             // 0
-            ("src/models/sol.obj", 0u32),
+            (Some("src/models/sol.obj"), Shape::Icosphere { subdivisions: 5 }),
             // 1
-            ("src/models/mini_planeta_1.obj", 1u32),
+            (Some("src/models/mini_planeta_1.obj"), Shape::CubeSphere { resolution: 24 }),
             // 2
-            ("src/models/nave.obj", 2u32),
+            (Some("src/models/nave.obj"), Shape::Spaceship),
             //3
-            ("src/models/mini_planeta_2.obj", 3u32),
+            (Some("src/models/mini_planeta_2.obj"), Shape::UvSphere { stacks: 48, slices: 96 }),
             //4
-            ("src/models/mini_planeta_3.obj", 4u32),
+            (Some("src/models/mini_planeta_3.obj"), Shape::CubeSphere { resolution: 32 }),
             //5
-            ("src/models/huevo_planeta.obj", 5u32),
+            (Some("src/models/huevo_planeta.obj"), Shape::Icosphere { subdivisions: 4 }),
             //6
-            ("src/models/luna.obj", 6u32),
+            (Some("src/models/luna.obj"), Shape::CubeSphere { resolution: 16 }),
         ];
 
         let mut models = Vec::new();
         let mut original_vertices = Vec::new();
         let mut dynamic_vertices = Vec::new();
 
-        for (i, (path, planet_id)) in paths.iter().enumerate() {
-            let (verts, inds) = load_obj(path, *planet_id);
+        for (i, (path, shape)) in bodies.iter().enumerate() {
+            let planet_id = i as u32;
+            let loaded = path.map(|p| (p, load_obj(p, planet_id)));
+            let (verts, inds) = match loaded {
+                Some((_, Ok(mesh))) => mesh,
+                Some((p, Err(e))) => {
+                    println!("No se pudo cargar {} ({}), usando malla generada", p, e);
+                    generate_shape(*shape, planet_positions[i].1, planet_id)
+                }
+                None => generate_shape(*shape, planet_positions[i].1, planet_id),
+            };
 
             // guardamos copias para animación
             original_vertices.push(verts.clone());
@@ -68,190 +114,234 @@ impl Scene {
             });
         }
 
-        // ⭐ órbitas independientes
+        // ⭐ órbitas independientes (los padres van antes que sus lunas)
         let orbits = vec![
-              // órbita del planeta 1
-            Scene::make_orbit(12.0),  //orbita planeta 1
-            Scene::make_orbit(40.0),  // orbita planeta 2
-            Scene::make_orbit(60.0),  // orbita planeta 3
-            Scene::make_orbit(75.0),  // orbita planeta huevo
+            Orbit { body: 1, parent: None, radius: 12.0, speed: 0.3, line: Some(OrbitStyle { color: [0.9, 0.6, 0.4, 0.9], width: 2.0, fade: true }) },    // planeta 1
+            Orbit { body: 2, parent: None, radius: 30.0, speed: 0.09, line: None },                                                           // nave
+            Orbit { body: 3, parent: None, radius: 40.0, speed: 0.03, line: Some(OrbitStyle { color: [0.5, 0.7, 1.0, 0.9], width: 2.0, fade: true }) },   // planeta 2
+            Orbit { body: 4, parent: None, radius: 60.0, speed: 0.09, line: Some(OrbitStyle { color: [0.7, 0.9, 1.0, 0.9], width: 2.0, fade: true }) },   // planeta 3
+            Orbit { body: 5, parent: None, radius: 75.0, speed: 0.07, line: Some(OrbitStyle { color: [1.0, 0.7, 0.3, 0.9], width: 2.0, fade: true }) },   // planeta huevo
+            Orbit { body: 6, parent: Some(5), radius: 10.0, speed: 0.4, line: Some(OrbitStyle { color: [0.8, 0.8, 0.8, 0.6], width: 1.5, fade: false }) }, // luna del huevo
+        ];
+
+        // Estelas: (cuerpo, color, muestras, muestras por segundo)
+        let trails = vec![
+            Trail::new(2, [0.4, 0.9, 1.0], 240, 20.0), // nave (~12 s)
+            Trail::new(6, [0.9, 0.9, 0.8], 300, 10.0), // luna (~30 s)
+        ];
+
+        // Atmósferas opcionales por cuerpo (coeficientes por grosor de capa)
+        let atmospheres = vec![
+            Atmosphere { body: 3, height: 0.25, rayleigh: [0.2, 0.45, 1.1], mie: 0.1, mie_g: 0.76, intensity: 20.0 },  // planeta 2: cielo azul
+            Atmosphere { body: 4, height: 0.2, rayleigh: [0.3, 0.7, 0.9], mie: 0.08, mie_g: 0.7, intensity: 18.0 },   // planeta 3: helado
+            Atmosphere { body: 5, height: 0.3, rayleigh: [0.8, 0.45, 0.15], mie: 0.2, mie_g: 0.8, intensity: 16.0 },   // planeta huevo: neblina ocre
+        ];
+
+        // Superficie procedural de cada cuerpo (body = planet_id)
+        let surfaces = vec![
+            Surface { body: 0, kind: SurfaceKind::Star, seed: 0.0, frequency: 4.0, warp: 0.8, ..Default::default() },
+            Surface {
+                // planeta 1: roca seca y cordilleras
+                body: 1, seed: 1.0, frequency: 2.0, sea_level: -1.0, ridges: 0.8,
+                land: [0.45, 0.35, 0.25], highland: [0.7, 0.6, 0.5],
+                ..Default::default()
+            },
+            Surface { body: 2, kind: SurfaceKind::Hull, ..Default::default() },
+            Surface {
+                // planeta 2: gigante gaseoso
+                body: 3, kind: SurfaceKind::GasGiant, seed: 3.0, frequency: 1.2, warp: 1.0,
+                ocean: [0.4, 0.6, 0.8], land: [0.9, 0.7, 0.4], highland: [0.95, 0.9, 0.8],
+                ..Default::default()
+            },
+            Surface {
+                // planeta 3: mares helados y casquetes extensos
+                body: 4, seed: 4.0, sea_level: 0.1, ice_caps: 0.55, clouds: 0.3,
+                ocean: [0.2, 0.35, 0.55], land: [0.6, 0.7, 0.8], highland: [0.85, 0.9, 0.95],
+                ..Default::default()
+            },
+            Surface {
+                // planeta huevo: desierto ocre con mares de lava oscura y
+                // jirones de ceniza que giran al revés que los del planeta 3
+                body: 5, seed: 5.0, sea_level: -0.1, warp: 0.9,
+                clouds: 0.3, cloud_height: 0.08, cloud_speed: -0.05,
+                ocean: [0.35, 0.08, 0.03], land: [0.9, 0.5, 0.2], highland: [1.0, 0.8, 0.4],
+                ..Default::default()
+            },
+            Surface {
+                // luna: mares basálticos y tierras altas grises
+                body: 6, seed: 6.0, frequency: 2.5, sea_level: -0.3, warp: 0.3, ridges: 0.6,
+                ocean: [0.3, 0.3, 0.3], land: [0.6, 0.6, 0.58], highland: [0.8, 0.8, 0.77],
+                ..Default::default()
+            },
+            Surface {
+                // rocas del cinturón: sin mares, grises y pardas
+                body: ASTEROID_SURFACE, seed: 7.0, frequency: 3.0, octaves: 4, sea_level: -1.0, warp: 0.3, ridges: 0.5, ice_caps: 2.0,
+                land: [0.35, 0.32, 0.3], highland: [0.55, 0.5, 0.45],
+                ..Default::default()
+            },
+        ];
+
+        // Relieve con LOD; estos cuerpos se dibujan con el terreno en vez de su malla
+        let terrains = vec![
+            Terrain { body: 1, source: HeightSource::Noise, amplitude: 0.06, max_depth: 7, split_distance: 2.5 }, // planeta 1
+            Terrain { body: 4, source: HeightSource::Noise, amplitude: 0.03, max_depth: 7, split_distance: 2.5 }, // planeta 3
+            Terrain { body: 5, source: HeightSource::Noise, amplitude: 0.04, max_depth: 7, split_distance: 2.5 }, // planeta huevo
+            Terrain { body: 6, source: HeightSource::Heightmap("src/scenes/luna_altura.png"), amplitude: 0.05, max_depth: 6, split_distance: 2.5 }, // luna
+        ];
+
+        // Cinturón entre las órbitas del planeta 2 y el planeta 3
+        let belt = AsteroidBelt {
+            count: 4000,
+            inner_radius: 46.0,
+            outer_radius: 54.0,
+            max_inclination: 0.05,
+            max_eccentricity: 0.08,
+            min_size: 0.05,
+            max_size: 0.45,
+            density: 0.02,
+            seed: 42,
+        };
+
+        // Cometas: órbitas muy excéntricas que cruzan el sistema
+        let comets = vec![
+            Comet {
+                elements: KeplerElements { semi_major_axis: 70.0, eccentricity: 0.85, inclination: 0.2, node: 0.5, periapsis: 1.0, mean_anomaly: -0.3 },
+                size: 0.4, tail_length: 30.0, activity_radius: 20.0,
+            },
+            Comet {
+                elements: KeplerElements { semi_major_axis: 55.0, eccentricity: 0.88, inclination: -0.45, node: 3.5, periapsis: 0.2, mean_anomaly: 3.1 },
+                size: 0.3, tail_length: 24.0, activity_radius: 15.0,
+            },
+            Comet {
+                elements: KeplerElements { semi_major_axis: 90.0, eccentricity: 0.8, inclination: 0.7, node: 2.1, periapsis: 4.4, mean_anomaly: 5.2 },
+                size: 0.5, tail_length: 40.0, activity_radius: 25.0,
+            },
         ];
 
         Self {
             models,
             orbits,
-            planet_positions: vec![
-                (Vec3::ZERO, 5.0),           // Sol
-                (Vec3::ZERO, 1.5),           // Planeta 1
-                (Vec3::ZERO, 1.0),           // Nave
-                (Vec3::ZERO, 2.0),           // Planeta 2
-                (Vec3::ZERO, 2.5),           // Planeta 3
-                (Vec3::ZERO, 1.8),           // Planeta huevo
-                (Vec3::ZERO, 0.8),           // Luna
-            ],
-            ship_rot: (0.0, 0.0),
+            trails,
+            atmospheres,
+            surfaces,
+            terrains,
+            belt,
+            comets,
+            planet_positions,
+            ship_rot: (0.0, 0.0, 0.0),
+            nbody_scene: None,
+            nbody: None,
             original_vertices,
             dynamic_vertices,
         }
     }
 
-    /// Genera una órbita circular
-    fn make_orbit(radius: f32) -> Vec<Vec3> {
-        (0..=128)
-            .map(|j| {
-                let a = j as f32 / 128.0 * std::f32::consts::TAU;
-                Vec3::new(a.cos() * radius, 0.0, a.sin() * radius)
-            })
-            .collect()
-    }
-
-    pub fn update(&mut self, time: f32, queue: &wgpu::Queue) {
-
-        //planeta 1 -----------------------------------------------------------
-        let radius = 12.0;
-        let angle = time * 0.3;
-        let ox = angle.cos() * radius;
-        let oz = angle.sin() * radius;
-
-        for (orig, dynv) in self.original_vertices[1]
-            .iter()
-            .zip(self.dynamic_vertices[1].iter_mut())
-        {
-            dynv.pos[0] = orig.pos[0] + ox;
-            dynv.pos[2] = orig.pos[2] + oz;
+    /// Mueve los cuerpos hasta `time`. Con `shadow_casters` a false no se
+    /// suben las mallas de los cuerpos con terreno, que solo se usan para
+    /// proyectar sombras.
+    pub fn update(&mut self, time: f32, dt: f32, queue: &wgpu::Queue, shadow_casters: bool) {
+        let mut moved = Vec::new();
+
+        if let Some(sim) = &mut self.nbody {
+            sim.step(dt);
+            for (i, pos) in sim.positions() {
+                self.planet_positions[i].0 = pos;
+                moved.push(i);
+            }
         }
 
-        // Actualizar posición para colisiones
-        self.planet_positions[1] = (Vec3::new(ox, 0.0, oz), 1.5);
-
-        queue.write_buffer(
-            &self.models[1].vb,
-            0,
-            bytemuck::cast_slice(&self.dynamic_vertices[1]),
-        );
-
-        
-
-        
-        // nave 2 -----------------------------------------------------------
-        let radius4 = 30.0;
-        let a4 = time * 0.09;
-
-        let ox2 = a4.cos() * radius4;
-        let oz2 = a4.sin() * radius4;
-
-        for (orig, dynv) in self.original_vertices[2]
-            .iter()
-            .zip(self.dynamic_vertices[2].iter_mut())
-        {
-            dynv.pos[0] = orig.pos[0] + ox2;
-            dynv.pos[2] = orig.pos[2] + oz2;
+        for orbit in &self.orbits {
+            if self.nbody.as_ref().is_some_and(|sim| sim.simulates(orbit.body)) {
+                continue;
+            }
+            let center = orbit.parent.map_or(Vec3::ZERO, |p| self.planet_positions[p].0);
+            let angle = time * orbit.speed;
+            let pos = center + Vec3::new(angle.cos(), 0.0, angle.sin()) * orbit.radius;
+
+            // Actualizar posición para colisiones
+            self.planet_positions[orbit.body].0 = pos;
+            moved.push(orbit.body);
         }
 
-        // Actualizar posición para colisiones
-        self.planet_positions[2] = (Vec3::new(ox2, 0.0, oz2), 1.0);
-
-        queue.write_buffer(
-            &self.models[2].vb,
-            0,
-            bytemuck::cast_slice(&self.dynamic_vertices[2]),
-        );
-        
-       
-        // planeta 2 -----------------------------------------------------------
-        let radius4 = 40.0;
-        let a4 = time * 0.03;
-
-        let ox3 = a4.cos() * radius4;
-        let oz3 = a4.sin() * radius4;
-
-        for (orig, dynv) in self.original_vertices[3]
-            .iter()
-            .zip(self.dynamic_vertices[3].iter_mut())
-        {
-            dynv.pos[0] = orig.pos[0] + ox3;
-            dynv.pos[2] = orig.pos[2] + oz3;
+        for i in moved {
+            // Los cuerpos con terreno se dibujan con sus chunks, no con la malla
+            if !shadow_casters && self.has_terrain(i) {
+                continue;
+            }
+            let pos = self.planet_positions[i].0;
+            for (orig, dynv) in self.original_vertices[i]
+                .iter()
+                .zip(self.dynamic_vertices[i].iter_mut())
+            {
+                dynv.pos[0] = orig.pos[0] + pos.x;
+                dynv.pos[1] = orig.pos[1] + pos.y;
+                dynv.pos[2] = orig.pos[2] + pos.z;
+            }
+
+            queue.write_buffer(
+                &self.models[i].vb,
+                0,
+                bytemuck::cast_slice(&self.dynamic_vertices[i]),
+            );
         }
+    }
 
-        // Actualizar posición para colisiones
-        self.planet_positions[3] = (Vec3::new(ox3, 0.0, oz3), 2.0);
-
-        queue.write_buffer(
-            &self.models[3].vb,
-            0,
-            bytemuck::cast_slice(&self.dynamic_vertices[3]),
-        );
-
-
-        // planeta 3 -----------------------------------------------------------
-        let radius4 = 60.0;
-        let a4 = time * 0.09;
-
-        let ox4 = a4.cos() * radius4;
-        let oz4 = a4.sin() * radius4;
-
-        for (orig, dynv) in self.original_vertices[4]
-            .iter()
-            .zip(self.dynamic_vertices[4].iter_mut())
-        {
-            dynv.pos[0] = orig.pos[0] + ox4;
-            dynv.pos[2] = orig.pos[2] + oz4;
+    /// Lee la escena de N cuerpos; sin ella la tecla N no hace nada
+    pub fn load_nbody(&mut self, path: &str) {
+        match NBodyScene::load(path) {
+            Ok(scene) => self.nbody_scene = Some(scene),
+            Err(e) => println!("No se pudo cargar {} ({}), modo N-cuerpos no disponible", path, e),
         }
+    }
 
-        // Actualizar posición para colisiones
-        self.planet_positions[4] = (Vec3::new(ox4, 0.0, oz4), 2.5);
-
-        queue.write_buffer(
-            &self.models[4].vb,
-            0,
-            bytemuck::cast_slice(&self.dynamic_vertices[4]),
-        );
-
-        // planeta huevo  -----------------------------------------------------------
-        let radius4 = 75.0;
-        let a4 = time * 0.07;
+    /// Choques del modo N-cuerpos desde la última llamada
+    pub fn take_collisions(&mut self) -> Vec<Collision> {
+        self.nbody.as_mut().map_or_else(Vec::new, NBodySim::take_collisions)
+    }
 
-        let ox5 = a4.cos() * radius4;
-        let oz5 = a4.sin() * radius4;
+    /// Los cuerpos se mueven por gravedad mutua en vez de por sus órbitas fijas
+    pub fn nbody_active(&self) -> bool {
+        self.nbody.is_some()
+    }
 
-        for (orig, dynv) in self.original_vertices[5]
-            .iter()
-            .zip(self.dynamic_vertices[5].iter_mut())
-        {
-            dynv.pos[0] = orig.pos[0] + ox5;
-            dynv.pos[2] = orig.pos[2] + oz5;
+    pub fn process_action(&mut self, action: Action, pressed: bool) {
+        if pressed && action == Action::ToggleNBody {
+            self.toggle_nbody();
         }
+    }
 
-        // Actualizar posición para colisiones
-        self.planet_positions[5] = (Vec3::new(ox5, 0.0, oz5), 1.8);
-
-        //orbita de la luna en el planeta huevo
-        queue.write_buffer(
-            &self.models[5].vb,
-            0,
-            bytemuck::cast_slice(&self.dynamic_vertices[5]),
-        );
-        let moon_radius = 10.0;     // distancia alrededor del huevo
-        let moon_speed = time * 0.4; // velocidad más alta
-
-        let moon_x = ox5 + moon_speed.cos() * moon_radius;
-        let moon_z = oz5 + moon_speed.sin() * moon_radius;
-
-        for (orig, dynv) in self.original_vertices[6]
-            .iter()
-            .zip(self.dynamic_vertices[6].iter_mut())
-        {
-            dynv.pos[0] = orig.pos[0] + moon_x;
-            dynv.pos[2] = orig.pos[2] + moon_z;
+    /// Al activarlo la simulación parte siempre del estado del archivo
+    fn toggle_nbody(&mut self) {
+        if self.nbody.take().is_none() {
+            let Some(scene) = &self.nbody_scene else {
+                println!("Modo N-cuerpos: no hay escena cargada");
+                return;
+            };
+            let radii: Vec<f32> = self.planet_positions.iter().map(|&(_, r)| r).collect();
+            match NBodySim::new(scene, &radii) {
+                Ok(sim) => self.nbody = Some(sim),
+                Err(e) => {
+                    println!("Modo N-cuerpos: escena no válida ({})", e);
+                    return;
+                }
+            }
         }
+        // Las estelas viejas unirían con una línea el salto de posición
+        for trail in &mut self.trails {
+            trail.clear();
+        }
+        println!("Modo N-cuerpos: {}", if self.nbody.is_some() { "ACTIVADO" } else { "DESACTIVADO" });
+    }
 
-        // Actualizar posición para colisiones
-        self.planet_positions[6] = (Vec3::new(moon_x, 0.0, moon_z), 0.8);
+    /// El cuerpo se dibuja como terreno con LOD en lugar de con su malla
+    pub fn has_terrain(&self, body: usize) -> bool {
+        self.terrains.iter().any(|t| t.body == body)
+    }
 
-        queue.write_buffer(
-            &self.models[6].vb,
-            0,
-            bytemuck::cast_slice(&self.dynamic_vertices[6]),
-        );
+    /// Registra la posición actual de cada cuerpo con estela
+    pub fn record_trails(&mut self, dt: f32) {
+        for trail in &mut self.trails {
+            trail.record(dt, self.planet_positions[trail.body].0);
+        }
     }
-}
\ No newline at end of file
+}
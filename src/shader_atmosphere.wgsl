@@ -0,0 +1,153 @@
+struct Globals {
+    view_proj: mat4x4<f32>,
+    time: f32,
+    // tres f32 sueltos: un vec3 se alinearía a 16 bytes y no coincide con Rust
+    _pad0a: f32,
+    _pad0b: f32,
+    _pad0c: f32,
+    viewport: vec4<f32>, // (ancho, alto, 1/ancho, 1/alto)
+    camera_pos: vec4<f32>, // w sin usar
+};
+@group(0) @binding(0)
+var<uniform> globals: Globals;
+
+struct AtmosphereParams {
+    center: vec4<f32>,   // w = radio del planeta
+    rayleigh: vec4<f32>, // w = radio de la atmósfera
+    mie: vec4<f32>,      // coeficiente, g, intensidad del sol
+    sun_pos: vec4<f32>,
+};
+@group(1) @binding(0)
+var<storage, read> atmospheres: array<AtmosphereParams>;
+
+const PI: f32 = 3.14159265;
+const VIEW_STEPS: i32 = 12;
+const LIGHT_STEPS: i32 = 4;
+// Alturas de escala en fracción del grosor de la capa
+const RAYLEIGH_SCALE: f32 = 0.25;
+const MIE_SCALE: f32 = 0.1;
+
+struct VertexIn {
+    @builtin(instance_index) instance: u32,
+    @location(0) pos: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) planet_id: u32,
+};
+
+struct VSOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) world_pos: vec3<f32>,
+    @location(1) @interpolate(flat) instance: u32,
+};
+
+@vertex
+fn vs_main(in: VertexIn) -> VSOut {
+    let atm = atmospheres[in.instance];
+    var out: VSOut;
+    out.world_pos = atm.center.xyz + in.pos * atm.rayleigh.w;
+    out.clip_pos = globals.view_proj * vec4<f32>(out.world_pos, 1.0);
+    out.instance = in.instance;
+    return out;
+}
+
+// Distancias de entrada y salida del rayo en la esfera (x > y si no la corta)
+fn ray_sphere(ro: vec3<f32>, rd: vec3<f32>, center: vec3<f32>, radius: f32) -> vec2<f32> {
+    let oc = ro - center;
+    let b = dot(oc, rd);
+    let c = dot(oc, oc) - radius * radius;
+    let h = b * b - c;
+    if (h < 0.0) {
+        return vec2<f32>(1e9, -1e9);
+    }
+    let s = sqrt(h);
+    return vec2<f32>(-b - s, -b + s);
+}
+
+// Profundidad óptica (Rayleigh, Mie) desde `p` hasta salir de la capa hacia `dir`
+fn light_depth(p: vec3<f32>, dir: vec3<f32>, atm: AtmosphereParams, hr: f32, hm: f32) -> vec2<f32> {
+    let len = ray_sphere(p, dir, atm.center.xyz, atm.rayleigh.w).y;
+    let ds = max(len, 0.0) / f32(LIGHT_STEPS);
+    var depth = vec2<f32>(0.0);
+    for (var j = 0; j < LIGHT_STEPS; j = j + 1) {
+        let q = p + dir * (f32(j) + 0.5) * ds;
+        let h = max(length(q - atm.center.xyz) - atm.center.w, 0.0);
+        depth += vec2<f32>(exp(-h / hr), exp(-h / hm)) * ds;
+    }
+    return depth;
+}
+
+@fragment
+fn fs_main(in: VSOut, @builtin(front_facing) front: bool) -> @location(0) vec4<f32> {
+    let atm = atmospheres[in.instance];
+    let center = atm.center.xyz;
+    let planet_r = atm.center.w;
+    let atm_r = atm.rayleigh.w;
+
+    let cam = globals.camera_pos.xyz;
+    let rd = normalize(in.world_pos - cam);
+
+    // Desde fuera cuenta la cara frontal; desde dentro, la trasera
+    let inside = length(cam - center) < atm_r;
+    if (front == inside) {
+        discard;
+    }
+
+    let hit = ray_sphere(cam, rd, center, atm_r);
+    let t_start = max(hit.x, 0.0);
+    var t_end = hit.y;
+    let ground = ray_sphere(cam, rd, center, planet_r);
+    if (ground.x > 0.0 && ground.x < ground.y) {
+        t_end = min(t_end, ground.x);
+    }
+    if (t_end <= t_start) {
+        discard;
+    }
+
+    let thickness = atm_r - planet_r;
+    let hr = thickness * RAYLEIGH_SCALE;
+    let hm = thickness * MIE_SCALE;
+    let beta_r = atm.rayleigh.xyz / thickness;
+    let beta_m = atm.mie.x / thickness;
+
+    let ds = (t_end - t_start) / f32(VIEW_STEPS);
+    var view_depth = vec2<f32>(0.0);
+    var sum_r = vec3<f32>(0.0);
+    var sum_m = vec3<f32>(0.0);
+
+    for (var i = 0; i < VIEW_STEPS; i = i + 1) {
+        let p = cam + rd * (t_start + (f32(i) + 0.5) * ds);
+        let h = max(length(p - center) - planet_r, 0.0);
+        let density = vec2<f32>(exp(-h / hr), exp(-h / hm)) * ds;
+        view_depth += density;
+
+        // Muestras en la sombra del propio planeta no reciben luz
+        let to_sun = normalize(atm.sun_pos.xyz - p);
+        let occluder = ray_sphere(p, to_sun, center, planet_r);
+        if (occluder.y > 0.0 && occluder.x < occluder.y) {
+            continue;
+        }
+
+        let sun_depth = light_depth(p, to_sun, atm, hr, hm);
+        let depth = view_depth + sun_depth;
+        let tau = beta_r * depth.x + vec3<f32>(beta_m * 1.1 * depth.y);
+        let attenuation = exp(-tau);
+        sum_r += attenuation * density.x;
+        sum_m += attenuation * density.y;
+    }
+
+    // Fases: el ángulo con el sol apenas varía a lo largo del rayo
+    let sun_dir = normalize(atm.sun_pos.xyz - center);
+    let mu = dot(rd, sun_dir);
+    let phase_r = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+    let g = atm.mie.y;
+    let phase_m = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+        / ((2.0 + g * g) * pow(max(1.0 + g * g - 2.0 * g * mu, 1e-4), 1.5));
+
+    let color = atm.mie.z * (sum_r * beta_r * phase_r + sum_m * beta_m * phase_m);
+
+    // Lo que hay detrás se atenúa con la transmitancia media del rayo
+    let transmittance = exp(-(beta_r * view_depth.x + vec3<f32>(beta_m * 1.1 * view_depth.y)));
+    let alpha = 1.0 - dot(transmittance, vec3<f32>(1.0 / 3.0));
+    return vec4<f32>(color, alpha);
+}
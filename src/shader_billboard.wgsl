@@ -0,0 +1,35 @@
+// Billboards aditivos (ver particles.rs): se antepone a los shaders de las
+// partículas y de las colas de los cometas. Cada billboard son 6 vértices.
+
+// Esquina -1..1 del vértice `vertex` del billboard
+fn billboard_corner(vertex: u32) -> vec2<f32> {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+    return corners[vertex % 6u];
+}
+
+// Rectángulo girado hacia `eye` y alargado según `axis` (la velocidad, la
+// dirección de la cola...): `half_len` a lo largo del eje y `half_width` de
+// través. Con el eje apuntando a la cámara queda como un cuadrado.
+fn billboard_point(center: vec3<f32>, axis: vec3<f32>, eye: vec3<f32>, corner: vec2<f32>, half_len: f32, half_width: f32) -> vec3<f32> {
+    let to_cam = normalize(eye - center);
+    var across = cross(axis, to_cam);
+    if (dot(across, across) < 1e-6) {
+        across = cross(vec3<f32>(0.0, 1.0, 0.0), to_cam);
+    }
+    across = normalize(across);
+    let along = cross(to_cam, across);
+    return center + along * (corner.y * half_len) + across * (corner.x * half_width);
+}
+
+// Brillo gaussiano desde el centro; fuera del círculo inscrito se descarta.
+// Con mezcla aditiva el alfa no cuenta, solo el color por este brillo.
+fn billboard_falloff(uv: vec2<f32>) -> f32 {
+    let d = dot(uv, uv);
+    if (d > 1.0) {
+        discard;
+    }
+    return exp(-4.0 * d);
+}
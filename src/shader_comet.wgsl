@@ -0,0 +1,112 @@
+// Coma y colas de los cometas (ver comets.rs). Cada instancia es un cometa
+// y cada grupo de 6 vértices una partícula: la primera es la coma y el resto
+// se reparte entre la cola de iones (recta, opuesta al sol) y la de polvo
+// (más ancha y curvada hacia atrás respecto al movimiento). Las partículas
+// no tienen estado: su edad sale del tiempo y de un hash de su índice, así
+// que fluyen desde el núcleo sin buffers que actualizar. Se dibujan con los
+// billboards de shader_billboard.wgsl, que se antepone a este archivo.
+
+struct Globals {
+    view_proj: mat4x4<f32>,
+    time: f32,
+    _pad0a: f32,
+    _pad0b: f32,
+    _pad0c: f32,
+    viewport: vec4<f32>, // (ancho, alto, 1/ancho, 1/alto)
+    camera_pos: vec4<f32>, // w sin usar
+};
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+// Debe coincidir con comets.rs; el resto de partículas son de polvo
+const ION_PARTICLES: u32 = 160u;
+const PARTICLE_LIFETIME: f32 = 6.0; // segundos en recorrer la cola
+
+struct CometIn {
+    @builtin(vertex_index) vertex: u32,
+    @builtin(instance_index) comet: u32,
+    @location(0) nucleus: vec4<f32>,  // w = radio del núcleo
+    @location(1) anti_sun: vec4<f32>, // w = longitud de las colas
+    @location(2) motion: vec4<f32>,   // w = actividad
+};
+
+struct TailOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) uv: vec2<f32>, // -1..1 dentro de la partícula
+    @location(1) color: vec3<f32>,
+};
+
+fn hash(n: f32) -> f32 {
+    return fract(sin(n * 12.9898) * 43758.5453);
+}
+
+@vertex
+fn vs_tail(in: CometIn) -> TailOut {
+    let corner = billboard_corner(in.vertex);
+    let k = in.vertex / 6u;
+
+    let nucleus = in.nucleus.xyz;
+    let away = in.anti_sun.xyz;
+    let tail_len = in.anti_sun.w;
+    let activity = in.motion.w;
+
+    // Base perpendicular a la cola para dispersar las partículas
+    var side = cross(away, in.motion.xyz);
+    if (dot(side, side) < 1e-6) {
+        side = cross(away, vec3<f32>(0.0, 1.0, 0.0));
+    }
+    side = normalize(side);
+    let up = cross(side, away);
+
+    var center = nucleus;
+    var axis = away;
+    var half_len: f32;
+    var half_width: f32;
+    var color: vec3<f32>;
+
+    if (k == 0u) {
+        // Coma: un halo alrededor del núcleo que crece con la actividad
+        half_len = in.nucleus.w * (1.5 + 5.0 * activity);
+        half_width = half_len;
+        color = vec3<f32>(0.7, 0.85, 1.0) * (0.1 + 0.6 * activity);
+    } else {
+        let seed = f32(k) + f32(in.comet) * 1013.0;
+        let age = fract(hash(seed) + globals.time / PARTICLE_LIFETIME);
+        let jitter = vec2<f32>(hash(seed + 1.7), hash(seed + 4.3)) * 2.0 - 1.0;
+
+        if (k <= ION_PARTICLES) {
+            // Iones: arrastrados por el viento solar en línea recta, finos
+            center = nucleus + away * (age * tail_len * 1.3)
+                + (side * jitter.x + up * jitter.y) * (age * tail_len * 0.03);
+            half_len = tail_len * 0.06;
+            half_width = in.nucleus.w * 0.3 + tail_len * 0.004;
+            color = vec3<f32>(0.3, 0.55, 1.0);
+        } else {
+            // Polvo: se queda atrás en la órbita, así que la cola se curva
+            // y se abre en un abanico sobre el plano del movimiento
+            axis = normalize(away - in.motion.xyz * (0.8 * age));
+            center = nucleus + axis * (age * tail_len)
+                + (side * jitter.x + up * jitter.y * 0.25) * (age * tail_len * 0.12);
+            half_len = tail_len * (0.01 + 0.03 * age) + in.nucleus.w;
+            half_width = half_len * 0.6;
+            color = vec3<f32>(1.0, 0.8, 0.55) * 0.5;
+        }
+
+        // Aparece junto al núcleo y se apaga al final de la cola
+        let fade = (1.0 - age) * smoothstep(0.0, 0.05, age);
+        color = color * (activity * fade * 0.35);
+    }
+
+    // Rectángulo alargado según la cola y girado hacia la cámara
+    let world = billboard_point(center, axis, globals.camera_pos.xyz, corner, half_len, half_width);
+
+    var out: TailOut;
+    out.clip_pos = globals.view_proj * vec4<f32>(world, 1.0);
+    out.uv = corner;
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_tail(in: TailOut) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color * billboard_falloff(in.uv), 0.0);
+}
@@ -0,0 +1,112 @@
+struct HdrUniforms {
+    exposure: f32,
+    bloom_intensity: f32,
+    threshold: f32,
+    knee: f32,
+    tonemap_op: u32,  // 0 = ACES, 1 = Reinhard, 2 = sin tone mapping
+    encode_srgb: u32, // la salida no es *Srgb: aplicar la curva a mano
+};
+
+@group(0) @binding(0) var input_tex: texture_2d<f32>;
+@group(0) @binding(1) var input_sampler: sampler;
+@group(0) @binding(2) var<uniform> params: HdrUniforms;
+@group(0) @binding(3) var bloom_tex: texture_2d<f32>;
+
+struct VSOut {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Triángulo que cubre toda la pantalla
+@vertex fn vs_fullscreen(@builtin(vertex_index) idx: u32) -> VSOut {
+    let uv = vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u));
+    var out: VSOut;
+    out.pos = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn luminance(c: vec3<f32>) -> f32 {
+    return dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+// Caja de 4 muestras bilineales (16 texels) alrededor de uv
+fn box4(tex: texture_2d<f32>, uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(tex));
+    let o = texel * 0.5;
+    var c = textureSample(tex, input_sampler, uv + vec2<f32>(-o.x, -o.y)).rgb;
+    c += textureSample(tex, input_sampler, uv + vec2<f32>(o.x, -o.y)).rgb;
+    c += textureSample(tex, input_sampler, uv + vec2<f32>(-o.x, o.y)).rgb;
+    c += textureSample(tex, input_sampler, uv + vec2<f32>(o.x, o.y)).rgb;
+    return c * 0.25;
+}
+
+// Umbral con rodilla suave: solo pasan las zonas brillantes (sol, emisivos)
+@fragment fn fs_prefilter(in: VSOut) -> @location(0) vec4<f32> {
+    let c = box4(input_tex, in.uv);
+    let brightness = max(c.r, max(c.g, c.b));
+    let knee = params.threshold * params.knee;
+    var soft = clamp(brightness - params.threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 1e-4);
+    let contribution = max(soft, brightness - params.threshold) / max(brightness, 1e-4);
+    return vec4<f32>(c * contribution, 1.0);
+}
+
+@fragment fn fs_downsample(in: VSOut) -> @location(0) vec4<f32> {
+    return vec4<f32>(box4(input_tex, in.uv), 1.0);
+}
+
+// Filtro tienda 3x3 sobre el nivel más pequeño; se suma al nivel destino
+@fragment fn fs_upsample(in: VSOut) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(input_tex));
+    var c = textureSample(input_tex, input_sampler, in.uv).rgb * 4.0;
+    c += textureSample(input_tex, input_sampler, in.uv + vec2<f32>(texel.x, 0.0)).rgb * 2.0;
+    c += textureSample(input_tex, input_sampler, in.uv - vec2<f32>(texel.x, 0.0)).rgb * 2.0;
+    c += textureSample(input_tex, input_sampler, in.uv + vec2<f32>(0.0, texel.y)).rgb * 2.0;
+    c += textureSample(input_tex, input_sampler, in.uv - vec2<f32>(0.0, texel.y)).rgb * 2.0;
+    c += textureSample(input_tex, input_sampler, in.uv + texel).rgb;
+    c += textureSample(input_tex, input_sampler, in.uv - texel).rgb;
+    c += textureSample(input_tex, input_sampler, in.uv + vec2<f32>(texel.x, -texel.y)).rgb;
+    c += textureSample(input_tex, input_sampler, in.uv + vec2<f32>(-texel.x, texel.y)).rgb;
+    return vec4<f32>(c / 16.0, 1.0);
+}
+
+// Aproximación de ACES de Krzysztof Narkowicz
+fn aces(x: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((x * (a * x + b)) / (x * (c * x + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn reinhard(x: vec3<f32>) -> vec3<f32> {
+    // Sobre la luminancia para no desaturar los colores
+    let l = luminance(x);
+    return x / (1.0 + l);
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let lo = c * 12.92;
+    let hi = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(hi, lo, c <= vec3<f32>(0.0031308));
+}
+
+@fragment fn fs_tonemap(in: VSOut) -> @location(0) vec4<f32> {
+    let scene = textureSample(input_tex, input_sampler, in.uv).rgb;
+    let bloom = textureSample(bloom_tex, input_sampler, in.uv).rgb;
+    let hdr = (scene + bloom * params.bloom_intensity) * params.exposure;
+
+    var ldr: vec3<f32>;
+    switch (params.tonemap_op) {
+        case 0u: { ldr = aces(hdr); }
+        case 1u: { ldr = reinhard(hdr); }
+        default: { ldr = clamp(hdr, vec3<f32>(0.0), vec3<f32>(1.0)); }
+    }
+
+    if (params.encode_srgb == 1u) {
+        ldr = linear_to_srgb(clamp(ldr, vec3<f32>(0.0), vec3<f32>(1.0)));
+    }
+    return vec4<f32>(ldr, 1.0);
+}
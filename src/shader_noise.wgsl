@@ -0,0 +1,92 @@
+// Biblioteca de ruido 3D compartida; se antepone a los shaders que la usan.
+// Todas las funciones devuelven valores aproximadamente en [-1, 1] salvo
+// que se indique lo contrario. Las octavas conviene pasarlas desde datos
+// uniformes: con un número constante el compilador de GL desenrolla e
+// inlinea cada bucle y la creación del pipeline se vuelve lentísima.
+
+// Hash entero pcg3d: estable en cualquier backend, sin depender de sin()
+fn pcg3d(v_in: vec3<u32>) -> vec3<u32> {
+    var v = v_in * 1664525u + 1013904223u;
+    v.x += v.y * v.z;
+    v.y += v.z * v.x;
+    v.z += v.x * v.y;
+    v ^= v >> vec3<u32>(16u);
+    v.x += v.y * v.z;
+    v.y += v.z * v.x;
+    v.z += v.x * v.y;
+    return v;
+}
+
+// Gradiente pseudoaleatorio de una celda de la rejilla
+fn noise_gradient(cell: vec3<f32>) -> vec3<f32> {
+    let h = pcg3d(bitcast<vec3<u32>>(vec3<i32>(cell)));
+    return vec3<f32>(h) * (2.0 / 4294967295.0) - 1.0;
+}
+
+// Desplazamiento del dominio que distingue a cada semilla
+fn noise_seed(seed: f32) -> vec3<f32> {
+    return vec3<f32>(17.13, 31.71, 47.37) * seed;
+}
+
+// Ruido de gradiente (Perlin) con interpolación quíntica
+fn noise3(p: vec3<f32>) -> f32 {
+    let i = floor(p);
+    let f = p - i;
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+
+    let n000 = dot(noise_gradient(i + vec3<f32>(0.0, 0.0, 0.0)), f - vec3<f32>(0.0, 0.0, 0.0));
+    let n100 = dot(noise_gradient(i + vec3<f32>(1.0, 0.0, 0.0)), f - vec3<f32>(1.0, 0.0, 0.0));
+    let n010 = dot(noise_gradient(i + vec3<f32>(0.0, 1.0, 0.0)), f - vec3<f32>(0.0, 1.0, 0.0));
+    let n110 = dot(noise_gradient(i + vec3<f32>(1.0, 1.0, 0.0)), f - vec3<f32>(1.0, 1.0, 0.0));
+    let n001 = dot(noise_gradient(i + vec3<f32>(0.0, 0.0, 1.0)), f - vec3<f32>(0.0, 0.0, 1.0));
+    let n101 = dot(noise_gradient(i + vec3<f32>(1.0, 0.0, 1.0)), f - vec3<f32>(1.0, 0.0, 1.0));
+    let n011 = dot(noise_gradient(i + vec3<f32>(0.0, 1.0, 1.0)), f - vec3<f32>(0.0, 1.0, 1.0));
+    let n111 = dot(noise_gradient(i + vec3<f32>(1.0, 1.0, 1.0)), f - vec3<f32>(1.0, 1.0, 1.0));
+
+    let x00 = mix(n000, n100, u.x);
+    let x10 = mix(n010, n110, u.x);
+    let x01 = mix(n001, n101, u.x);
+    let x11 = mix(n011, n111, u.x);
+    return mix(mix(x00, x10, u.y), mix(x01, x11, u.y), u.z);
+}
+
+// Suma de octavas: cada una al doble de frecuencia y la mitad de amplitud
+fn fbm(p: vec3<f32>, octaves: i32) -> f32 {
+    var sum = 0.0;
+    var amp = 0.5;
+    var q = p;
+    for (var i = 0; i < octaves; i = i + 1) {
+        sum += amp * noise3(q);
+        q = q * 2.03 + vec3<f32>(1.7, 9.2, 5.3); // desfase para romper la alineación
+        amp *= 0.5;
+    }
+    return sum * 2.0;
+}
+
+// Ruido ridged en [0, 1]: crestas afiladas donde el ruido cruza el cero;
+// cada octava se pondera con la anterior para concentrar el detalle en las crestas
+fn ridged(p: vec3<f32>, octaves: i32) -> f32 {
+    var sum = 0.0;
+    var amp = 0.5;
+    var weight = 1.0;
+    var q = p;
+    for (var i = 0; i < octaves; i = i + 1) {
+        var r = 1.0 - abs(noise3(q));
+        r = r * r * weight;
+        weight = clamp(r * 2.0, 0.0, 1.0);
+        sum += amp * r;
+        q = q * 2.03 + vec3<f32>(4.1, 2.7, 8.3);
+        amp *= 0.5;
+    }
+    return sum;
+}
+
+// Domain warping: desplaza `p` con tres fbm independientes
+fn domain_warp(p: vec3<f32>, amount: f32, octaves: i32) -> vec3<f32> {
+    let offset = vec3<f32>(
+        fbm(p + vec3<f32>(0.0, 0.0, 0.0), octaves),
+        fbm(p + vec3<f32>(5.2, 1.3, 2.8), octaves),
+        fbm(p + vec3<f32>(1.7, 9.2, 4.6), octaves),
+    );
+    return p + offset * amount;
+}
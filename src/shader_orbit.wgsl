@@ -1,21 +1,143 @@
 struct Globals {
     view_proj: mat4x4<f32>,
     time: f32,
-    _pad0: vec3<f32>,
-    _pad1: vec4<f32>,
-    _pad2: vec4<f32>,
+    // tres f32 sueltos: un vec3 se alinearía a 16 bytes y no coincide con Rust
+    _pad0a: f32,
+    _pad0b: f32,
+    _pad0c: f32,
+    viewport: vec4<f32>, // (ancho, alto, 1/ancho, 1/alto)
+    camera_pos: vec4<f32>, // w sin usar
 };
 @group(0) @binding(0)
 var<uniform> globals: Globals;
 
-struct VSIn { @location(0) position: vec3<f32> };
+struct OrbitParams {
+    color: vec4<f32>,
+    center: vec4<f32>,   // w = radio
+    body_pos: vec4<f32>, // w = 1.0 si se desvanece
+    width: f32,          // grosor en píxeles
+};
+@group(1) @binding(0)
+var<storage, read> orbits: array<OrbitParams>;
+
+// Una instancia por segmento (a -> b), 6 vértices por quad
+struct SegmentIn {
+    @builtin(vertex_index) corner: u32,
+    @location(0) a: vec3<f32>,
+    @location(1) a_orbit: u32,
+    @location(2) b: vec3<f32>,
+    @location(3) b_orbit: u32,
+};
+
+struct VSOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) world_pos: vec3<f32>,
+    @location(1) edge: f32, // distancia al eje de la línea, en píxeles
+    @location(2) @interpolate(flat) orbit: u32,
+};
+
+// Recorta el extremo `p` contra el plano cercano en dirección a `q`
+fn clip_near(p: vec4<f32>, q: vec4<f32>) -> vec4<f32> {
+    let near_w = 1e-3;
+    if (p.w >= near_w) {
+        return p;
+    }
+    let t = (near_w - p.w) / (q.w - p.w);
+    return mix(p, q, t);
+}
 
 @vertex
-fn vs_main(in: VSIn) -> @builtin(position) vec4<f32> {
-    return globals.view_proj * vec4<f32>(in.position, 1.0);
+fn vs_main(in: SegmentIn) -> VSOut {
+    var out: VSOut;
+
+    // Segmento entre dos órbitas distintas: se descarta como triángulo degenerado
+    if (in.a_orbit != in.b_orbit) {
+        out.clip_pos = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        out.world_pos = vec3<f32>(0.0);
+        out.edge = 0.0;
+        out.orbit = in.a_orbit;
+        return out;
+    }
+
+    let params = orbits[in.a_orbit];
+    let world_a = params.center.xyz + in.a;
+    let world_b = params.center.xyz + in.b;
+
+    let raw_a = globals.view_proj * vec4<f32>(world_a, 1.0);
+    let raw_b = globals.view_proj * vec4<f32>(world_b, 1.0);
+    let clip_a = clip_near(raw_a, raw_b);
+    let clip_b = clip_near(raw_b, raw_a);
+
+    // Dirección del segmento en píxeles y su normal en pantalla
+    let screen_a = clip_a.xy / clip_a.w * globals.viewport.xy;
+    let screen_b = clip_b.xy / clip_b.w * globals.viewport.xy;
+    var dir = screen_b - screen_a;
+    if (dot(dir, dir) < 1e-8) {
+        dir = vec2<f32>(1.0, 0.0);
+    }
+    dir = normalize(dir);
+    let normal = vec2<f32>(-dir.y, dir.x);
+
+    // Esquinas: (0,-), (1,-), (0,+), (0,+), (1,-), (1,+)
+    var ends = array<f32, 6>(0.0, 1.0, 0.0, 0.0, 1.0, 1.0);
+    var sides = array<f32, 6>(-1.0, -1.0, 1.0, 1.0, -1.0, 1.0);
+    let t = ends[in.corner];
+    let side = sides[in.corner];
+
+    // Un píxel extra a cada lado para el antialiasing: la cobertura llega a
+    // cero medio píxel fuera de la línea y el resto es margen
+    let half_width = params.width * 0.5 + 1.0;
+    var clip = mix(clip_a, clip_b, t);
+    // De píxeles a NDC: 2 / tamaño del viewport
+    let offset = normal * side * half_width * 2.0 * globals.viewport.zw;
+    clip = vec4<f32>(clip.xy + offset * clip.w, clip.zw);
+
+    out.clip_pos = clip;
+    out.world_pos = mix(world_a, world_b, t);
+    out.edge = side * half_width;
+    out.orbit = in.a_orbit;
+    return out;
 }
 
 @fragment
-fn fs_main() -> @location(0) vec4<f32> {
-    return vec4<f32>(0.8, 0.8, 1.0, 1.0);
-}
\ No newline at end of file
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let params = orbits[in.orbit];
+    var alpha = params.color.a;
+
+    // Cobertura analítica: borde suave de ~1 píxel
+    let coverage = clamp(params.width * 0.5 + 0.5 - abs(in.edge), 0.0, 1.0);
+    alpha = alpha * coverage;
+
+    // Más intenso cerca del cuerpo, tenue en el lado opuesto de la órbita
+    if (params.body_pos.w > 0.5) {
+        let d = distance(in.world_pos, params.body_pos.xyz) / (2.0 * params.center.w);
+        alpha = alpha * mix(1.0, 0.15, smoothstep(0.0, 1.0, d));
+    }
+
+    return vec4<f32>(params.color.rgb, alpha);
+}
+
+// ---------- Estelas (color por vértice, desvanecidas en CPU) ----------
+
+struct TrailIn {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct TrailOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_trail(in: TrailIn) -> TrailOut {
+    var out: TrailOut;
+    out.clip_pos = globals.view_proj * vec4<f32>(in.position, 1.0);
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_trail(in: TrailOut) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,140 @@
+// Sistema de partículas (ver particles.rs). cs_update mueve las partículas
+// en el buffer de almacenamiento y vs_particle/fs_particle dibujan el mismo
+// buffer como billboards aditivos (shader_billboard.wgsl, antepuesto), una
+// instancia por partícula.
+
+struct Globals {
+    view_proj: mat4x4<f32>,
+    time: f32,
+    _pad0a: f32,
+    _pad0b: f32,
+    _pad0c: f32,
+    viewport: vec4<f32>, // (ancho, alto, 1/ancho, 1/alto)
+    camera_pos: vec4<f32>, // w sin usar
+};
+
+// Debe coincidir con Particle en particles.rs
+struct Particle {
+    position: vec4<f32>,    // w = edad
+    velocity: vec4<f32>,    // w = vida total
+    color_start: vec4<f32>, // w = tamaño inicial
+    color_end: vec4<f32>,   // w = tamaño final
+    motion: vec4<f32>,      // x = frenado, y = alargamiento
+};
+
+struct UpdateParams {
+    dt: f32,
+    count: u32,
+    _pad0: u32,
+    _pad1: u32,
+};
+
+struct Occluders {
+    spheres: array<vec4<f32>, 16>, // centro + radio
+    count: u32,
+};
+
+// Bindings del compute; el render usa otros para compartir el módulo
+@group(0) @binding(1) var<uniform> params: UpdateParams;
+@group(0) @binding(2) var<storage, read_write> particles: array<Particle>;
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(1) @binding(0) var<uniform> occluders: Occluders;
+
+@compute @workgroup_size(64)
+fn cs_update(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.count) {
+        return;
+    }
+    var p = particles[i];
+    // Muertas (o huecos sin estrenar del buffer): nada que mover
+    if (p.position.w >= p.velocity.w) {
+        return;
+    }
+    let dt = params.dt;
+    let vel = p.velocity.xyz * max(1.0 - p.motion.x * dt, 0.0);
+    p.position = vec4<f32>(p.position.xyz + vel * dt, p.position.w + dt);
+    p.velocity = vec4<f32>(vel, p.velocity.w);
+    particles[i] = p;
+}
+
+struct ParticleIn {
+    @builtin(vertex_index) vertex: u32,
+    @location(0) position: vec4<f32>,
+    @location(1) velocity: vec4<f32>,
+    @location(2) color_start: vec4<f32>,
+    @location(3) color_end: vec4<f32>,
+    @location(4) motion: vec4<f32>,
+};
+
+struct ParticleOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) uv: vec2<f32>, // -1..1 dentro de la partícula
+    @location(1) color: vec3<f32>,
+    @location(2) world_pos: vec3<f32>,
+    @location(3) size: f32,
+};
+
+@vertex
+fn vs_particle(in: ParticleIn) -> ParticleOut {
+    var out: ParticleOut;
+    let life = in.velocity.w;
+    if (in.position.w >= life) {
+        // Triángulo degenerado fuera del volumen de vista
+        out.clip_pos = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+        out.uv = vec2<f32>(0.0);
+        out.color = vec3<f32>(0.0);
+        out.world_pos = vec3<f32>(0.0);
+        out.size = 1.0;
+        return out;
+    }
+
+    let corner = billboard_corner(in.vertex);
+
+    let t = in.position.w / life;
+    let size = mix(in.color_start.w, in.color_end.w, t);
+    // Aparece rápido y se apaga al final de su vida
+    let fade = smoothstep(0.0, 0.05, t) * (1.0 - t);
+    let color = mix(in.color_start.rgb, in.color_end.rgb, t) * fade;
+
+    // Billboard alargado en la dirección del movimiento
+    let vel = in.velocity.xyz;
+    let half_len = size + length(vel) * in.motion.y;
+    let world = billboard_point(in.position.xyz, vel, globals.camera_pos.xyz, corner, half_len, size);
+
+    out.clip_pos = globals.view_proj * vec4<f32>(world, 1.0);
+    out.uv = corner;
+    out.color = color;
+    out.world_pos = world;
+    out.size = size;
+    return out;
+}
+
+@fragment
+fn fs_particle(in: ParticleOut) -> @location(0) vec4<f32> {
+    let falloff = billboard_falloff(in.uv);
+
+    // Suavizado: en lugar de leer el depth, se corta el rayo de vista contra
+    // las esferas de los cuerpos y la partícula se apaga al acercarse a la
+    // superficie que tiene delante, sin el borde duro del depth test
+    let eye = globals.camera_pos.xyz;
+    let ray = in.world_pos - eye;
+    let dist = length(ray);
+    let dir = ray / dist;
+    var soft = 1.0;
+    for (var i = 0u; i < occluders.count; i = i + 1u) {
+        let s = occluders.spheres[i];
+        let oc = eye - s.xyz;
+        let b = dot(oc, dir);
+        let h = b * b - (dot(oc, oc) - s.w * s.w);
+        if (h > 0.0) {
+            let hit = -b - sqrt(h);
+            if (hit > 0.0) {
+                soft = min(soft, clamp((hit - dist) / in.size, 0.0, 1.0));
+            }
+        }
+    }
+
+    return vec4<f32>(in.color * (falloff * soft), 0.0);
+}
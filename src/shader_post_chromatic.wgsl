@@ -0,0 +1,12 @@
+// Aberración cromática: params.x = desplazamiento máximo en píxeles (en los bordes)
+
+@fragment fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let from_center = in.uv - vec2<f32>(0.5);
+    // Crece con la distancia al centro
+    let offset = from_center * length(from_center) * 2.0 * post.params.x / post.resolution;
+
+    let r = textureSample(input_tex, input_sampler, in.uv + offset).r;
+    let g = textureSample(input_tex, input_sampler, in.uv).g;
+    let b = textureSample(input_tex, input_sampler, in.uv - offset).b;
+    return vec4<f32>(r, g, b, 1.0);
+}
@@ -0,0 +1,25 @@
+// Prefijo común de los efectos de post-procesado: cada efecto añade su fs_main
+
+struct PostUniforms {
+    params: vec4<f32>,     // parámetros propios de cada efecto
+    resolution: vec2<f32>,
+    time: f32,
+};
+
+@group(0) @binding(0) var input_tex: texture_2d<f32>;
+@group(0) @binding(1) var input_sampler: sampler;
+@group(0) @binding(2) var<uniform> post: PostUniforms;
+
+struct VSOut {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Triángulo que cubre toda la pantalla
+@vertex fn vs_fullscreen(@builtin(vertex_index) idx: u32) -> VSOut {
+    let uv = vec2<f32>(f32((idx << 1u) & 2u), f32(idx & 2u));
+    var out: VSOut;
+    out.pos = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
@@ -0,0 +1,18 @@
+// Gradación de color: params = (contraste, saturación, temperatura, _)
+
+@fragment fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    var c = textureSample(input_tex, input_sampler, in.uv).rgb;
+
+    // Contraste alrededor del gris medio
+    c = (c - 0.5) * post.params.x + 0.5;
+
+    // Saturación respecto a la luminancia
+    let l = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = mix(vec3<f32>(l), c, post.params.y);
+
+    // Temperatura: > 0 más cálido, < 0 más frío
+    let t = post.params.z;
+    c = c * vec3<f32>(1.0 + t * 0.1, 1.0, 1.0 - t * 0.1);
+
+    return vec4<f32>(clamp(c, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
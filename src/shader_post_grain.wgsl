@@ -0,0 +1,20 @@
+// Grano de película: params.x = intensidad
+
+fn hash12(p: vec2<f32>) -> f32 {
+    var p3 = fract(vec3<f32>(p.xyx) * 0.1031);
+    p3 = p3 + dot(p3, p3.yzx + 33.33);
+    return fract((p3.x + p3.y) * p3.z);
+}
+
+@fragment fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let c = textureSample(input_tex, input_sampler, in.uv).rgb;
+
+    // Nuevo patrón cada ~1/24 s, como el grano de una película
+    let frame = floor(post.time * 24.0);
+    let n = hash12(in.pos.xy + frame * 17.0) - 0.5;
+
+    // Más visible en tonos medios que en negros y blancos
+    let l = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let weight = 1.0 - abs(l * 2.0 - 1.0);
+    return vec4<f32>(c + vec3<f32>(n * post.params.x * weight), 1.0);
+}
@@ -0,0 +1,12 @@
+// Viñeta: params = (intensidad, radio, suavidad, _)
+
+@fragment fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let c = textureSample(input_tex, input_sampler, in.uv).rgb;
+
+    // Distancia al centro corrigiendo el aspecto para que sea circular
+    let aspect = post.resolution.x / post.resolution.y;
+    let d = length((in.uv - vec2<f32>(0.5)) * vec2<f32>(aspect, 1.0));
+    let v = smoothstep(post.params.y, post.params.y + post.params.z, d);
+
+    return vec4<f32>(c * (1.0 - v * post.params.x), 1.0);
+}
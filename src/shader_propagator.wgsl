@@ -0,0 +1,103 @@
+// Propagación orbital en la GPU (ver propagator.rs). Un hilo por cuerpo:
+// - cs_kepler resuelve la ecuación de Kepler para el instante actual
+// - cs_kick / cs_drift forman un paso leapfrog (kick-drift-kick) bajo la
+//   gravedad del sol, fijo en el origen, y la de los demás cuerpos
+// El resultado se escribe en el buffer de instancias que se dibuja.
+
+struct Params {
+    time: f32,
+    dt: f32,        // paso de integración (N-cuerpos)
+    mu: f32,        // G·M del sol
+    softening: f32, // evita fuerzas infinitas en encuentros cercanos
+    count: u32,
+    _pad0: u32,
+    _pad1: u32,
+    _pad2: u32,
+};
+
+struct BodyState {
+    p: vec4<f32>,         // dirección del periapsis, w = semieje mayor
+    q: vec4<f32>,         // perpendicular en el plano de la órbita, w = excentricidad
+    motion: vec4<f32>,    // movimiento medio, anomalía media inicial, giro, masa
+    spin_axis: vec4<f32>, // w = tamaño
+    velocity: vec4<f32>,
+};
+
+struct BodyInstance {
+    position: vec4<f32>, // w = tamaño
+    rotation: vec4<f32>, // cuaternión (x, y, z, w)
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> states: array<BodyState>;
+@group(0) @binding(2) var<storage, read_write> instances: array<BodyInstance>;
+
+fn spin_rotation(s: BodyState) -> vec4<f32> {
+    let half = s.motion.z * params.time * 0.5;
+    return vec4<f32>(s.spin_axis.xyz * sin(half), cos(half));
+}
+
+@compute @workgroup_size(64)
+fn cs_kepler(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.count) {
+        return;
+    }
+    let s = states[i];
+    let a = s.p.w;
+    let e = s.q.w;
+
+    // Newton sobre E - e·sin(E) = M; partir de π converge siempre, también
+    // en órbitas muy excéntricas
+    let tau = 6.2831853;
+    let m0 = s.motion.y + s.motion.x * params.time;
+    let m = m0 - floor(m0 / tau) * tau;
+    var ecc = select(m + e * sin(m), 3.1415927, e > 0.8);
+    for (var k = 0; k < 8; k = k + 1) {
+        ecc = ecc - (ecc - e * sin(ecc) - m) / (1.0 - e * cos(ecc));
+    }
+
+    let b = sqrt(1.0 - e * e);
+    let pos = s.p.xyz * (a * (cos(ecc) - e)) + s.q.xyz * (a * b * sin(ecc));
+    let r = a * (1.0 - e * cos(ecc));
+    let vel = (s.p.xyz * -sin(ecc) + s.q.xyz * (b * cos(ecc))) * (sqrt(params.mu * a) / r);
+
+    // La velocidad analítica permite pasar a N-cuerpos sin saltos
+    states[i].velocity = vec4<f32>(vel, 0.0);
+    instances[i] = BodyInstance(vec4<f32>(pos, s.spin_axis.w), spin_rotation(s));
+}
+
+@compute @workgroup_size(64)
+fn cs_kick(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.count) {
+        return;
+    }
+    let x = instances[i].position.xyz;
+    let eps2 = params.softening * params.softening;
+
+    let r2 = dot(x, x) + eps2;
+    var acc = -x * params.mu / (r2 * sqrt(r2));
+    for (var j = 0u; j < params.count; j = j + 1u) {
+        let mass = states[j].motion.w;
+        if (j == i || mass <= 0.0) {
+            continue;
+        }
+        let d = instances[j].position.xyz - x;
+        let d2 = dot(d, d) + eps2;
+        acc += d * mass / (d2 * sqrt(d2));
+    }
+
+    states[i].velocity = vec4<f32>(states[i].velocity.xyz + acc * (params.dt * 0.5), 0.0);
+}
+
+@compute @workgroup_size(64)
+fn cs_drift(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.count) {
+        return;
+    }
+    let s = states[i];
+    let pos = instances[i].position.xyz + s.velocity.xyz * params.dt;
+    instances[i] = BodyInstance(vec4<f32>(pos, s.spin_axis.w), spin_rotation(s));
+}
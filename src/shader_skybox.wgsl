@@ -1,9 +1,12 @@
 struct Globals {
     view_proj: mat4x4<f32>,
     time: f32,
-    _pad0: vec3<f32>,
-    _pad1: vec4<f32>,
-    _pad2: vec4<f32>,
+    // tres f32 sueltos: un vec3 se alinearía a 16 bytes y no coincide con Rust
+    _pad0a: f32,
+    _pad0b: f32,
+    _pad0c: f32,
+    viewport: vec4<f32>, // (ancho, alto, 1/ancho, 1/alto)
+    camera_pos: vec4<f32>, // w sin usar
 };
 
 @group(0) @binding(0) var<uniform> globals: Globals;
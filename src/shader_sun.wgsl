@@ -0,0 +1,207 @@
+struct Globals {
+    view_proj: mat4x4<f32>,
+    time: f32,
+    _pad0a: f32,
+    _pad0b: f32,
+    _pad0c: f32,
+    viewport: vec4<f32>, // (ancho, alto, 1/ancho, 1/alto)
+    camera_pos: vec4<f32>, // w sin usar
+};
+@group(0) @binding(0) var<uniform> globals: Globals;
+
+struct Sun {
+    center: vec4<f32>, // w = radio
+    color: vec4<f32>,  // rgb = color HDR, w = intensidad del destello
+    corona: vec4<f32>, // x = extensión de la corona en radios solares, y = visibilidad
+};
+@group(1) @binding(0) var<uniform> sun: Sun;
+
+fn hash11(p: f32) -> f32 {
+    return fract(sin(p * 127.1) * 43758.5453);
+}
+
+fn noise11(x: f32) -> f32 {
+    let i = floor(x);
+    let f = fract(x);
+    return mix(hash11(i), hash11(i + 1.0), f * f * (3.0 - 2.0 * f));
+}
+
+// ---------- Corona: billboard orientado a la cámara ----------
+
+struct CoronaOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) local: vec2<f32>, // en radios solares
+};
+
+@vertex fn vs_corona(@builtin(vertex_index) idx: u32) -> CoronaOut {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(-1.0, 1.0),
+        vec2<f32>(-1.0, 1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+    );
+    let corner = corners[idx];
+
+    let center = sun.center.xyz;
+    let to_sun = normalize(center - globals.camera_pos.xyz);
+    var right = cross(to_sun, vec3<f32>(0.0, 1.0, 0.0));
+    if (dot(right, right) < 1e-6) {
+        right = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    right = normalize(right);
+    let up = cross(right, to_sun);
+
+    let extent = sun.corona.x;
+    let world = center + (right * corner.x + up * corner.y) * sun.center.w * extent;
+
+    var out: CoronaOut;
+    out.clip_pos = globals.view_proj * vec4<f32>(world, 1.0);
+    out.local = corner * extent;
+    return out;
+}
+
+@fragment fn fs_corona(in: CoronaOut) -> @location(0) vec4<f32> {
+    let r = length(in.local);
+    if (r < 0.95 || r > sun.corona.x) {
+        discard;
+    }
+
+    // Caída radial desde el borde del disco
+    let falloff = pow(1.0 / r, 3.0);
+    let edge = 1.0 - smoothstep(1.0, sun.corona.x, r);
+
+    // Serpentinas: ruido angular animado
+    let angle = atan2(in.local.y, in.local.x);
+    let t = globals.time * 0.15;
+    let streamers = 0.6 + 0.4 * noise11(angle * 6.0 + t) * noise11(angle * 13.0 - t * 1.7);
+
+    let glow = falloff * edge * streamers;
+    return vec4<f32>(sun.color.rgb * glow, 1.0);
+}
+
+// ---------- Sondas de oclusión ----------
+
+// Quad de pocos píxeles sobre la superficie del sol; cada instancia es una
+// consulta de oclusión (ver sun.rs). Centro + anillos de 8 y 16 puntos.
+@vertex fn vs_probe(
+    @builtin(vertex_index) idx: u32,
+    @builtin(instance_index) probe: u32,
+) -> @builtin(position) vec4<f32> {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(-1.0, 1.0),
+        vec2<f32>(-1.0, 1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+    );
+
+    var disk = vec2<f32>(0.0);
+    if (probe > 0u && probe < 9u) {
+        let a = f32(probe - 1u) / 8.0 * 6.2831853;
+        disk = vec2<f32>(cos(a), sin(a)) * 0.45;
+    } else if (probe >= 9u) {
+        let a = f32(probe - 9u) / 16.0 * 6.2831853;
+        disk = vec2<f32>(cos(a), sin(a)) * 0.85;
+    }
+
+    let center = sun.center.xyz;
+    let radius = sun.center.w;
+    let to_sun = normalize(center - globals.camera_pos.xyz);
+    var right = cross(to_sun, vec3<f32>(0.0, 1.0, 0.0));
+    if (dot(right, right) < 1e-6) {
+        right = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    right = normalize(right);
+    let up = cross(right, to_sun);
+
+    // Punto de la cara visible de la esfera, adelantado un poco para que la
+    // malla del propio sol no lo tape
+    let h = sqrt(max(1.0 - dot(disk, disk), 0.0));
+    let surface = center + (right * disk.x + up * disk.y - to_sun * (h + 0.02)) * radius;
+
+    let clip = globals.view_proj * vec4<f32>(surface, 1.0);
+    let offset = corners[idx] * 1.5 * globals.viewport.zw * 2.0 * clip.w;
+    return vec4<f32>(clip.xy + offset, clip.zw);
+}
+
+@fragment fn fs_probe() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0);
+}
+
+// ---------- Destello de lente en espacio de pantalla ----------
+
+struct FlareOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) local: vec2<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) @interpolate(flat) ring: f32,
+};
+
+@vertex fn vs_flare(
+    @builtin(vertex_index) idx: u32,
+    @builtin(instance_index) element: u32,
+) -> FlareOut {
+    var out: FlareOut;
+    out.clip_pos = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    out.local = vec2<f32>(0.0);
+    out.color = vec3<f32>(0.0);
+    out.ring = 0.0;
+
+    let center = sun.center.xyz;
+    let clip = globals.view_proj * vec4<f32>(center, 1.0);
+    if (clip.w <= 0.0) {
+        return out; // sol detrás de la cámara
+    }
+    let ndc = clip.xy / clip.w;
+
+    var visibility = sun.corona.y;
+
+    // Desvanecer al salir de pantalla
+    let border = max(abs(ndc.x), abs(ndc.y));
+    visibility = visibility * (1.0 - smoothstep(0.9, 1.2, border));
+    if (visibility <= 0.0) {
+        return out;
+    }
+
+    // Elementos a lo largo del eje sol -> centro de pantalla (t = 0 en el sol)
+    var ts = array<f32, 7>(0.0, 0.4, 0.7, 1.2, 1.5, 1.8, 2.2);
+    var sizes = array<f32, 7>(0.35, 0.04, 0.08, 0.05, 0.12, 0.03, 0.18);
+    var colors = array<vec3<f32>, 7>(
+        vec3<f32>(1.0, 0.8, 0.5),
+        vec3<f32>(0.6, 1.0, 0.6),
+        vec3<f32>(0.5, 0.7, 1.0),
+        vec3<f32>(1.0, 0.6, 0.4),
+        vec3<f32>(0.5, 0.6, 1.0),
+        vec3<f32>(1.0, 1.0, 0.7),
+        vec3<f32>(0.7, 0.5, 1.0),
+    );
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(-1.0, 1.0),
+        vec2<f32>(-1.0, 1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+    );
+
+    let corner = corners[idx];
+    let pos = ndc * (1.0 - ts[element]);
+    let aspect = globals.viewport.x * globals.viewport.w;
+    let offset = corner * sizes[element] * vec2<f32>(1.0 / aspect, 1.0);
+
+    out.clip_pos = vec4<f32>(pos + offset, 0.0, 1.0);
+    out.local = corner;
+    out.color = colors[element] * visibility * sun.color.w;
+    // El halo sobre el sol es un disco suave; el último fantasma es un anillo
+    out.ring = select(0.0, 1.0, element == 6u);
+    if (element == 0u) {
+        out.color = out.color * 2.0;
+    } else {
+        out.color = out.color * 0.15;
+    }
+    return out;
+}
+
+@fragment fn fs_flare(in: FlareOut) -> @location(0) vec4<f32> {
+    let r = length(in.local);
+    if (r > 1.0) {
+        discard;
+    }
+    var shape = (1.0 - r) * (1.0 - r);
+    if (in.ring > 0.5) {
+        let d = (r - 0.85) * 12.0;
+        shape = exp(-d * d);
+    }
+    return vec4<f32>(in.color * shape, 1.0);
+}
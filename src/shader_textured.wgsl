@@ -1,15 +1,27 @@
 struct Globals {
     view_proj: mat4x4<f32>,
     time: f32,
-    _pad0: vec3<f32>,
-    _pad1: vec4<f32>,
-    _pad2: vec4<f32>,
+    // tres f32 sueltos: un vec3 se alinearía a 16 bytes y no coincide con Rust
+    _pad0a: f32,
+    _pad0b: f32,
+    _pad0c: f32,
+    viewport: vec4<f32>, // (ancho, alto, 1/ancho, 1/alto)
+    camera_pos: vec4<f32>, // w sin usar
 };
 
 @group(0) @binding(0) var<uniform> globals: Globals;
 @group(0) @binding(1) var planet_texture: texture_2d<f32>;
 @group(0) @binding(2) var planet_sampler: sampler;
 
+// Sombras de la luz puntual del sol (ver shadow.rs)
+struct Shadow {
+    light_pos: vec4<f32>,
+    params: vec4<f32>, // near, far, tamaño de texel a distancia 1, activado
+};
+@group(1) @binding(0) var<uniform> shadow: Shadow;
+@group(1) @binding(1) var shadow_map: texture_depth_cube;
+@group(1) @binding(2) var shadow_sampler: sampler_comparison;
+
 struct VSIn {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
@@ -22,119 +34,375 @@ struct VSOut {
     @location(0) normal: vec3<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) planet_id: u32,
+    @location(3) world_pos: vec3<f32>,
+    @location(4) dir: vec3<f32>, // dirección sin relieve: dominio del color
 };
 
 @vertex fn vs_main(in: VSIn) -> VSOut {
     var out: VSOut;
     out.clip_pos = globals.view_proj * vec4<f32>(in.position, 1.0);
+    out.world_pos = in.position; // los vértices ya están en mundo
     out.normal = normalize(in.normal);
+    out.dir = out.normal;
     out.uv = in.uv;
     out.planet_id = in.planet_id;
     return out;
 }
 
-// Funciones de color procedural para cada planeta
-fn sun_color(uv: vec2<f32>) -> vec3<f32> {
-    // Coordenadas centradas
-    let p = (uv - vec2<f32>(0.5)) * 2.0;
-    let r = length(p);
+// Superficie procedural de cada cuerpo (ver surface.rs); el ruido viene
+// de shader_noise.wgsl, que se antepone a este shader
+struct Surface {
+    kind: u32, // 0 estrella, 1 terrestre, 2 gigante gaseoso, 3 casco
+    seed: f32,
+    frequency: f32,
+    sea_level: f32,
+    warp: f32,
+    ridges: f32,
+    ice_caps: f32,
+    clouds: f32,
+    octaves: u32,
+    cloud_height: f32,
+    cloud_speed: f32,
+    ocean: vec4<f32>,
+    land: vec4<f32>,
+    highland: vec4<f32>,
+};
+@group(2) @binding(0) var<storage, read> surfaces: array<Surface>;
 
-    // Brillo central fuertísimo
-    let core = 1.5 * max(0.0, 1.0 - r * 1.2);
+// `base` es el fbm con domain warping común a todos los tipos; cada uno
+// añade como mucho un par de capas baratas. Mantener pocas llamadas al ruido
+// importa en GL, donde todo se inlinea en un único shader.
+fn star_color(base: f32) -> vec3<f32> {
+    // Granulado que hierve lentamente
+    return vec3<f32>(1.0, 0.55, 0.15) * (1.1 + base * 0.5);
+}
 
-    // “Plasma” animado
-    let t = globals.time * 1.5;
-    let wave1 = sin(p.x * 10.0 + t) * 0.4;
-    let wave2 = sin(p.y * 12.0 + t * 1.3) * 0.4;
-    let wave3 = sin((p.x + p.y) * 8.0 + t * 0.7) * 0.3;
+// Altura del relieve terrestre: el fbm común más las cordilleras
+fn terrestrial_height(q: vec3<f32>, base: f32, s: Surface) -> f32 {
+    return base + s.ridges * (ridged(q * 2.0, i32(s.octaves) - 2) - 0.5);
+}
 
-    let plasma = (wave1 + wave2 + wave3) * (1.0 - r);
+fn terrestrial_color(n: vec3<f32>, p: vec3<f32>, q: vec3<f32>, base: f32, s: Surface) -> vec3<f32> {
+    let octaves = i32(s.octaves);
+    let height = terrestrial_height(q, base, s);
 
-    // Corona externa (glow)
-    let corona = exp(-(r * 3.0)) * 0.8;
+    var color: vec3<f32>;
+    if (height < s.sea_level) {
+        // Más claro cerca de la costa
+        let depth = clamp((s.sea_level - height) * 4.0, 0.0, 1.0);
+        color = mix(s.ocean.rgb * 1.8, s.ocean.rgb, depth);
+    } else {
+        // Relativa al rango de tierra, para que los mundos secos no saturen
+        let elevation = clamp((height - s.sea_level) / (0.8 - s.sea_level), 0.0, 1.0);
+        color = mix(s.land.rgb, s.highland.rgb, elevation);
+    }
 
-    // Color base cálido
-    let base = vec3<f32>(1.0, 0.55, 0.15);
+    // Casquetes polares con borde irregular
+    let latitude = abs(n.y) + 0.08 * fbm(p * 3.0, octaves - 3);
+    let ice = smoothstep(s.ice_caps - 0.04, s.ice_caps + 0.04, latitude);
+    color = mix(color, vec3<f32>(0.9, 0.94, 1.0), ice);
+    return color;
+}
 
-    // Mezcla final (más brillante hacia el centro)
-    let intensity = core + plasma + corona;
+fn gas_giant_color(n: vec3<f32>, p: vec3<f32>, base: f32, s: Surface) -> vec3<f32> {
+    // Bandas por latitud deformadas por la turbulencia
+    let bands = sin((n.y + base * 0.12) * s.frequency * 10.0) * 0.5 + 0.5;
+    let detail = fbm(p * 4.0, i32(s.octaves) - 3) * 0.5 + 0.5;
+    let color = mix(s.ocean.rgb, s.land.rgb, bands);
+    return mix(color, s.highland.rgb, smoothstep(0.6, 0.9, detail) * 0.5);
+}
 
-    return base * intensity;
+fn hull_color(uv: vec2<f32>) -> vec3<f32> {
+    let metallic = sin(uv.x * 5.0) * 0.2 + 0.8;
+    return vec3<f32>(metallic * 0.6, metallic * 0.7, metallic * 0.9);
 }
 
+fn get_planet_color(planet_id: u32, normal: vec3<f32>, uv: vec2<f32>) -> vec3<f32> {
+    let s = surfaces[planet_id];
+    if (s.kind == 3u) {
+        return hull_color(uv);
+    }
+
+    // Los cuerpos no rotan: la normal en mundo es la de espacio de objeto
+    let n = normalize(normal);
+
+    // Dominio del ruido: los gaseosos se estiran en latitud para formar
+    // bandas, y las estrellas y los gaseosos se mueven con el tiempo
+    var stretch = vec3<f32>(1.0);
+    var drift = vec3<f32>(0.0);
+    if (s.kind == 0u) {
+        drift = vec3<f32>(0.0, globals.time * 0.15, 0.0);
+    } else if (s.kind == 2u) {
+        stretch = vec3<f32>(1.0, 3.0, 1.0);
+        drift = vec3<f32>(globals.time * 0.03, 0.0, 0.0);
+    }
+    let p = n * stretch * s.frequency + noise_seed(s.seed) + drift;
+    let q = domain_warp(p, s.warp, i32(s.octaves) - 2);
+    let base = fbm(q, i32(s.octaves));
 
-fn rock_planet_color(uv: vec2<f32>) -> vec3<f32> {
-    let pattern = sin(uv.x * 15.0) * cos(uv.y * 15.0);
-    let base = vec3<f32>(0.5, 0.4, 0.3);
-    return mix(base, base * 0.7, pattern * 0.5 + 0.5);
+    switch(s.kind) {
+        case 0u: { return star_color(base); }
+        case 1u: { return terrestrial_color(n, p, q, base, s); }
+        case 2u: { return gas_giant_color(n, p, base, s); }
+        default: { return vec3<f32>(1.0); }
+    }
 }
 
-fn gas_planet_color(uv: vec2<f32>) -> vec3<f32> {
-    let bands = sin(uv.y * 20.0) * 0.5 + 0.5;
-    let swirl = sin(uv.x * 10.0 - globals.time * 0.5) * 0.3;
-    return mix(
-        vec3<f32>(0.4, 0.6, 0.8),
-        vec3<f32>(0.9, 0.7, 0.4),
-        bands + swirl
-    );
+// Densidad de la capa de nubes (0..1) en la dirección `n` desde el centro
+// del cuerpo. La capa gira alrededor del eje Y a su propio ritmo; con menos
+// octavas sale más suave, que es lo que usa la sombra proyectada en el suelo.
+fn cloud_density(n: vec3<f32>, s: Surface, octaves: i32) -> f32 {
+    let a = globals.time * s.cloud_speed;
+    let drift = vec3<f32>(n.x * cos(a) - n.z * sin(a), n.y, n.x * sin(a) + n.z * cos(a));
+    let c = fbm(drift * 4.0 + noise_seed(s.seed + 1.0), octaves);
+    return smoothstep(1.0 - s.clouds * 1.5, 1.2 - s.clouds * 1.5, c * 0.5 + 0.5);
 }
 
-fn ice_planet_color(uv: vec2<f32>) -> vec3<f32> {
-    let pattern = cos(uv.x * 10.0) * sin(uv.y * 10.0);
-    let base = vec3<f32>(0.8, 0.9, 1.0);
-    return mix(base, vec3<f32>(0.5, 0.7, 0.9), abs(pattern) * 0.5);
+// Fracción de luz que atraviesa las nubes hasta el punto del suelo en la
+// dirección `n`: se cruza el rayo hacia el sol (`light`) con la capa, todo en
+// unidades del radio del cuerpo
+fn cloud_shadow(n: vec3<f32>, light: vec3<f32>, s: Surface) -> f32 {
+    if (s.clouds <= 0.0) {
+        return 1.0;
+    }
+    let b = dot(n, light);
+    let r = 1.0 + s.cloud_height;
+    let t = -b + sqrt(b * b - 1.0 + r * r);
+    let hit = normalize(n + light * t);
+    return 1.0 - cloud_density(hit, s, i32(s.octaves) - 3) * 0.7;
 }
 
-fn egg_planet_color(uv: vec2<f32>) -> vec3<f32> {
-    let glow = sin(globals.time) * 0.3 + 0.5;
-    let pattern = sin(uv.x * 25.0) * cos(uv.y * 15.0);
-    return mix(
-        vec3<f32>(0.9, 0.4, 0.2),
-        vec3<f32>(1.0, 0.8, 0.3),
-        pattern * 0.5 + glow * 0.3
-    );
+// Relieve desplazado en el vertex shader (ver terrain.rs)
+struct Terrain {
+    center: vec4<f32>, // w = radio
+    params: vec4<f32>, // amplitud, 1.0 si usa heightmap, cuerpo, sin usar
+};
+@group(3) @binding(0) var<uniform> terrain: Terrain;
+@group(3) @binding(1) var heightmap: texture_2d<f32>;
+@group(3) @binding(2) var heightmap_sampler: sampler;
+
+struct TerrainIn {
+    @location(0) st: vec2<f32>,
+    @location(1) skirt: f32,
+    @location(2) face: u32,
+    @location(3) origin: vec2<f32>,
+    @location(4) size: f32,
+};
+
+// Igual que mesh::cube_to_sphere
+fn cube_to_sphere(face: u32, st: vec2<f32>) -> vec3<f32> {
+    var normal: vec3<f32>;
+    var axis_u: vec3<f32>;
+    switch(face) {
+        case 0u: { normal = vec3<f32>(1.0, 0.0, 0.0); axis_u = vec3<f32>(0.0, 0.0, -1.0); }
+        case 1u: { normal = vec3<f32>(-1.0, 0.0, 0.0); axis_u = vec3<f32>(0.0, 0.0, 1.0); }
+        case 2u: { normal = vec3<f32>(0.0, 1.0, 0.0); axis_u = vec3<f32>(1.0, 0.0, 0.0); }
+        case 3u: { normal = vec3<f32>(0.0, -1.0, 0.0); axis_u = vec3<f32>(1.0, 0.0, 0.0); }
+        case 4u: { normal = vec3<f32>(0.0, 0.0, 1.0); axis_u = vec3<f32>(1.0, 0.0, 0.0); }
+        default: { normal = vec3<f32>(0.0, 0.0, -1.0); axis_u = vec3<f32>(-1.0, 0.0, 0.0); }
+    }
+    let axis_v = cross(normal, axis_u);
+    let c = normal + axis_u * (2.0 * st.x - 1.0) + axis_v * (2.0 * st.y - 1.0);
+    let c2 = c * c;
+    return normalize(c * sqrt(vec3<f32>(
+        1.0 - c2.y / 2.0 - c2.z / 2.0 + c2.y * c2.z / 3.0,
+        1.0 - c2.z / 2.0 - c2.x / 2.0 + c2.z * c2.x / 3.0,
+        1.0 - c2.x / 2.0 - c2.y / 2.0 + c2.x * c2.y / 3.0,
+    )));
 }
 
-fn moon_color(uv: vec2<f32>) -> vec3<f32> {
-    let craters = sin(uv.x * 30.0) * sin(uv.y * 30.0) * 0.2;
-    let base = vec3<f32>(0.8, 0.8, 0.75);
-    return base + vec3<f32>(craters);
+// Altura en 0..1 sobre el radio; los océanos quedan planos al nivel del mar
+// Celdas por lado de cada chunk (CHUNK_RESOLUTION en terrain.rs)
+const TERRAIN_CHUNK_RESOLUTION: f32 = 16.0;
+
+fn terrain_height(dir: vec3<f32>, s: Surface) -> f32 {
+    if (terrain.params.y > 0.5) {
+        let uv = vec2<f32>(fract(atan2(dir.z, dir.x) / 6.2831853), acos(clamp(dir.y, -1.0, 1.0)) / 3.14159265);
+        return textureSampleLevel(heightmap, heightmap_sampler, uv, 0.0).r;
+    }
+    let p = dir * s.frequency + noise_seed(s.seed);
+    let q = domain_warp(p, s.warp, i32(s.octaves) - 2);
+    let height = terrestrial_height(q, fbm(q, i32(s.octaves)), s);
+    // Con todo océano (sea_level = 1) no queda relieve que normalizar
+    let sea = min(s.sea_level, 0.99);
+    return (max(height, sea) - sea) / (1.0 - sea);
 }
 
-fn ship_color(uv: vec2<f32>) -> vec3<f32> {
-    let metallic = sin(uv.x * 5.0) * 0.2 + 0.8;
-    return vec3<f32>(metallic * 0.6, metallic * 0.7, metallic * 0.9);
+fn terrain_point(dir: vec3<f32>, s: Surface) -> vec3<f32> {
+    let radius = terrain.center.w * (1.0 + terrain.params.x * terrain_height(dir, s));
+    return terrain.center.xyz + dir * radius;
 }
 
-fn get_planet_color(planet_id: u32, uv: vec2<f32>) -> vec3<f32> {
-    switch(planet_id) {
-        case 0u: { return sun_color(uv); }
-        case 1u: { return rock_planet_color(uv); }
-        case 2u: { return ship_color(uv); }
-        case 3u: { return gas_planet_color(uv); }
-        case 4u: { return ice_planet_color(uv); }
-        case 5u: { return egg_planet_color(uv); }
-        case 6u: { return moon_color(uv); }
-        default: { return vec3<f32>(1.0); }
+@vertex fn vs_terrain(in: TerrainIn) -> VSOut {
+    let body = u32(terrain.params.z);
+    let s = surfaces[body];
+    let dir = cube_to_sphere(in.face, in.origin + in.st * in.size);
+
+    // Normal por diferencias finitas en el plano tangente, con el paso de la
+    // rejilla del chunk: más fino muestrearía detalle que la malla no tiene
+    var tangent = cross(dir, vec3<f32>(0.0, 1.0, 0.0));
+    if (dot(tangent, tangent) < 1e-6) {
+        tangent = vec3<f32>(1.0, 0.0, 0.0);
+    }
+    tangent = normalize(tangent);
+    let bitangent = cross(dir, tangent);
+    let eps = in.size * 1.5707963 / TERRAIN_CHUNK_RESOLUTION;
+    var world = terrain_point(dir, s);
+    let du = terrain_point(normalize(dir + tangent * eps), s) - world;
+    let dv = terrain_point(normalize(dir + bitangent * eps), s) - world;
+    var normal = normalize(cross(du, dv));
+    if (dot(normal, dir) < 0.0) {
+        normal = -normal;
+    }
+
+    // El faldón cuelga bajo el borde para tapar las grietas con el vecino
+    world -= dir * in.skirt * in.size * terrain.center.w * 0.1;
+
+    var out: VSOut;
+    out.clip_pos = globals.view_proj * vec4<f32>(world, 1.0);
+    out.world_pos = world;
+    out.normal = normal;
+    out.dir = dir;
+    out.uv = in.origin + in.st * in.size;
+    out.planet_id = body;
+    return out;
+}
+
+// Profundidad que guardó la cara del cubo correspondiente a `v` (relativo a
+// la luz): la distancia sobre el eje dominante pasada por la proyección
+fn shadow_depth(v: vec3<f32>) -> f32 {
+    let d = max(abs(v.x), max(abs(v.y), abs(v.z)));
+    let n = shadow.params.x;
+    let f = shadow.params.y;
+    return f * (d - n) / ((f - n) * d);
+}
+
+// 1 = iluminado, 0 = en sombra. PCF 3x3 en el plano perpendicular a la luz
+fn shadow_factor(world_pos: vec3<f32>, normal: vec3<f32>) -> f32 {
+    if (shadow.params.w < 0.5) {
+        return 1.0;
+    }
+
+    let dist = length(world_pos - shadow.light_pos.xyz);
+    let texel = shadow.params.z * dist;
+    // Desplazar por la normal evita el acné sin separar la sombra del objeto
+    let p = world_pos + normal * texel * 1.5 - shadow.light_pos.xyz;
+
+    let dir = normalize(p);
+    var t = cross(dir, vec3<f32>(0.0, 1.0, 0.0));
+    if (dot(t, t) < 1e-6) {
+        t = vec3<f32>(1.0, 0.0, 0.0);
     }
+    t = normalize(t);
+    let b = cross(dir, t);
+
+    var lit = 0.0;
+    for (var y: i32 = -1; y <= 1; y = y + 1) {
+        for (var x: i32 = -1; x <= 1; x = x + 1) {
+            let q = p + (t * f32(x) + b * f32(y)) * texel;
+            lit = lit + textureSampleCompareLevel(shadow_map, shadow_sampler, q, shadow_depth(q));
+        }
+    }
+    return lit / 9.0;
 }
 
 @fragment fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
-    let color = get_planet_color(in.planet_id, in.uv);
-    
-    let light = normalize(vec3<f32>(0.4, 0.8, 0.2));
+    let color = get_planet_color(in.planet_id, in.dir, in.uv);
+
+    // Luz puntual en el sol
+    let light = normalize(shadow.light_pos.xyz - in.world_pos);
     let diff = max(dot(in.normal, light), 0.0);
-    
+
     // El sol no necesita iluminación, otros sí
     var final_color: vec3<f32>;
     if (in.planet_id == 0u) {
-    
-        final_color = color * 1.8;
+        // Emisivo por encima de 1.0: el bloom lo hace brillar
+        final_color = color * 4.0;
     } else {
-        // Otros planetas sí reciben luz
-        final_color = color * (0.3 + diff * 0.7);
+        // Otros planetas sí reciben luz, salvo donde otro cuerpo tapa el sol.
+        // El desplazamiento contra el acné sigue la esfera base, que es lo
+        // que proyecta la sombra, y no la normal del relieve
+        let lit = shadow_factor(in.world_pos, in.dir) * cloud_shadow(in.dir, light, surfaces[in.planet_id]);
+        final_color = color * (0.1 + diff * 0.9 * lit);
     }
 
         
     return vec4<f32>(final_color, 1.0);
-}
\ No newline at end of file
+}
+
+// Capa de nubes (ver clouds.rs): esfera unitaria instanciada por cuerpo
+struct CloudIn {
+    @location(0) position: vec3<f32>,
+    @location(4) center: vec4<f32>, // w = radio de la capa
+    @location(5) body: u32,
+};
+
+struct CloudOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) world_pos: vec3<f32>,
+    @location(1) dir: vec3<f32>,
+    @location(2) @interpolate(flat) center: vec4<f32>,
+    @location(3) @interpolate(flat) body: u32,
+};
+
+@vertex fn vs_clouds(in: CloudIn) -> CloudOut {
+    let world = in.center.xyz + in.position * in.center.w;
+
+    var out: CloudOut;
+    out.clip_pos = globals.view_proj * vec4<f32>(world, 1.0);
+    out.world_pos = world;
+    out.dir = in.position;
+    out.center = in.center;
+    out.body = in.body;
+    return out;
+}
+
+// Color premultiplicado por la densidad. Desde fuera de la capa solo se
+// dibuja la cara exterior y desde dentro solo la interior
+@fragment fn fs_clouds(in: CloudOut, @builtin(front_facing) front: bool) -> @location(0) vec4<f32> {
+    let outside = length(globals.camera_pos.xyz - in.center.xyz) > in.center.w;
+    if (front != outside) {
+        discard;
+    }
+
+    let s = surfaces[in.body];
+    let dir = normalize(in.dir);
+    let density = cloud_density(dir, s, i32(s.octaves) - 1);
+
+    // Luz envolvente: las nubes dispersan algo de luz más allá del terminador
+    let light = normalize(shadow.light_pos.xyz - in.world_pos);
+    let diff = clamp(dot(dir, light) * 0.8 + 0.2, 0.0, 1.0);
+    let lit = shadow_factor(in.world_pos, dir);
+    let color = vec3<f32>(0.05 + diff * 0.95 * lit);
+    let alpha = density * 0.9;
+    return vec4<f32>(color * alpha, alpha);
+}
+
+// Cinturón de asteroides (ver asteroids.rs): transformación por instancia
+// escrita por el compute shader
+struct AsteroidIn {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(3) planet_id: u32,
+    @location(4) offset: vec4<f32>,   // w = escala
+    @location(5) rotation: vec4<f32>, // cuaternión
+};
+
+fn quat_rotate(q: vec4<f32>, v: vec3<f32>) -> vec3<f32> {
+    return v + 2.0 * cross(q.xyz, cross(q.xyz, v) + q.w * v);
+}
+
+@vertex fn vs_asteroid(in: AsteroidIn) -> VSOut {
+    let world = in.offset.xyz + quat_rotate(in.rotation, in.position) * in.offset.w;
+
+    var out: VSOut;
+    out.clip_pos = globals.view_proj * vec4<f32>(world, 1.0);
+    out.world_pos = world;
+    out.normal = quat_rotate(in.rotation, in.normal);
+    // El color va en espacio de objeto para que gire con la roca; las rocas
+    // no proyectan sombra, así que el desplazamiento de la sombra da igual
+    out.dir = normalize(in.position);
+    out.uv = vec2<f32>(0.0);
+    out.planet_id = in.planet_id;
+    return out;
+}
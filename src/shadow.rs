@@ -0,0 +1,299 @@
+use glam::{Mat4, Vec3};
+use crate::mesh::Vertex;
+use crate::scene::GpuModel;
+use crate::input::Action;
+
+/// Lado en texels de cada cara del cubo de sombras
+pub const SHADOW_RESOLUTION: u32 = 1024;
+
+// Cada cara usa su propia matriz en un slot de 256 bytes (offset dinámico)
+const FACE_STRIDE: wgpu::BufferAddress = 256;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowUniforms {
+    light_pos: [f32; 4],
+    params: [f32; 4], // near, far, tamaño de texel a distancia 1, activado
+}
+
+/// Mapa de sombras omnidireccional para la luz puntual del sol: un cubo de
+/// profundidad (6 capas) que se rellena cada frame con los cuerpos que
+/// proyectan sombra y se muestrea con PCF en el shader de los modelos.
+pub struct ShadowMap {
+    pub enabled: bool,
+    pub near: f32,
+    pub far: f32,
+    face_views: Vec<wgpu::TextureView>,
+    face_buf: wgpu::Buffer,
+    face_bg: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    uniform_buf: wgpu::Buffer,
+    pub layout: wgpu::BindGroupLayout, // grupo de sombras del pipeline de modelos
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl ShadowMap {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Cube"),
+            size: wgpu::Extent3d {
+                width: SHADOW_RESOLUTION,
+                height: SHADOW_RESOLUTION,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let face_views = (0..6)
+            .map(|face| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Shadow Face"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: face,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let cube_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Shadow Cube View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        // Pase de profundidad: una matriz por cara
+        let face_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Faces"),
+            size: FACE_STRIDE * 6,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let face_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Face Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<[[f32; 4]; 4]>() as u64),
+                },
+                count: None,
+            }],
+        });
+
+        let face_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Face BG"),
+            layout: &face_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &face_buf,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(std::mem::size_of::<[[f32; 4]; 4]>() as u64),
+                }),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shader_shadow.wgsl"));
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&face_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::layout()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: None,
+            // Sin culling: las caras se invierten en Y para seguir la convención del cubo
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        // Lado de muestreo, usado por el shader de los modelos
+        let uniform_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Uniforms"),
+            size: std::mem::size_of::<ShadowUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow BG"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buf.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&cube_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        Self {
+            enabled: true,
+            near: 1.0,
+            far: 300.0,
+            face_views,
+            face_buf,
+            face_bg,
+            pipeline,
+            uniform_buf,
+            layout,
+            bind_group,
+        }
+    }
+
+    /// Matriz de cada cara en el orden de capas del cubo (+X, -X, +Y, -Y, +Z, -Z).
+    /// look_at_rh produce la imagen invertida en Y respecto a la convención de
+    /// los cubemaps, así que se corrige en la proyección.
+    fn face_view_proj(&self, light: Vec3, face: usize) -> Mat4 {
+        let (dir, up) = [
+            (Vec3::X, Vec3::NEG_Y),
+            (Vec3::NEG_X, Vec3::NEG_Y),
+            (Vec3::Y, Vec3::Z),
+            (Vec3::NEG_Y, Vec3::NEG_Z),
+            (Vec3::Z, Vec3::NEG_Y),
+            (Vec3::NEG_Z, Vec3::NEG_Y),
+        ][face];
+        let flip = Mat4::from_scale(Vec3::new(1.0, -1.0, 1.0));
+        let proj = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, self.near, self.far);
+        flip * proj * Mat4::look_at_rh(light, light + dir, up)
+    }
+
+    pub fn process_action(&mut self, action: Action, pressed: bool) {
+        if pressed && action == Action::ToggleShadows {
+            self.enabled = !self.enabled;
+            println!("Sombras: {}", if self.enabled { "ACTIVADAS" } else { "DESACTIVADAS" });
+        }
+    }
+
+    /// Actualiza las matrices de las caras y los parámetros de muestreo
+    pub fn update(&self, queue: &wgpu::Queue, light: Vec3) {
+        for face in 0..6 {
+            let m = self.face_view_proj(light, face).to_cols_array_2d();
+            queue.write_buffer(&self.face_buf, face as u64 * FACE_STRIDE, bytemuck::bytes_of(&m));
+        }
+
+        let uniforms = ShadowUniforms {
+            light_pos: [light.x, light.y, light.z, 1.0],
+            params: [
+                self.near,
+                self.far,
+                2.0 / SHADOW_RESOLUTION as f32,
+                if self.enabled { 1.0 } else { 0.0 },
+            ],
+        };
+        queue.write_buffer(&self.uniform_buf, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    /// Rellena las 6 caras con `casters`; el sol, que es la luz, no debe estar
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, casters: &[GpuModel]) {
+        // Apagadas, el shader no lee el cubo
+        if !self.enabled {
+            return;
+        }
+
+        for (face, view) in self.face_views.iter().enumerate() {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.face_bg, &[(face as u64 * FACE_STRIDE) as u32]);
+            for model in casters {
+                pass.set_vertex_buffer(0, model.vb.slice(..));
+                pass.set_index_buffer(model.ib.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..model.icount, 0, 0..1);
+            }
+        }
+    }
+}
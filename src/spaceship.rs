@@ -1,5 +1,21 @@
+use glam::{Quat, Vec3};
 use crate::mesh::Vertex;
-use glam::Vec3;
+
+/// Punta del cono trasero (el propulsor) en coordenadas de la malla y
+/// dirección en la que sale el chorro
+pub const NOZZLE: Vec3 = Vec3::new(0.0, 0.0, -4.0);
+pub const NOZZLE_DIR: Vec3 = Vec3::NEG_Z;
+
+/// Giro que lleva el morro de la malla (+Z) a la dirección de vuelo
+pub fn orientation(forward: Vec3) -> Quat {
+    Quat::from_rotation_arc(Vec3::Z, forward.normalize())
+}
+
+/// Propulsor de la nave escalada a `radius` (la malla mide 4 hasta la cola)
+/// y girada con `orientation`, relativo al centro de la nave
+pub fn nozzle(radius: f32, orientation: Quat) -> Vec3 {
+    orientation * NOZZLE * radius / 4.0
+}
 
 pub fn generate_spaceship() -> (Vec<Vertex>, Vec<u32>) {
     let mut vertices = Vec::new();
@@ -43,12 +59,13 @@ pub fn generate_spaceship() -> (Vec<Vertex>, Vec<u32>) {
         });
     }
 
+    // Los anillos están intercalados: frente en 1, 3, 5... y atrás en 2, 4, 6...
     let body_front_start = 1;
-    let body_back_start = body_front_start + segments + 1;
+    let body_back_start = body_front_start + 1;
 
     // Cono trasero (propulsor)
     vertices.push(Vertex {
-        pos: [0.0, 0.0, -body_length - 1.0],
+        pos: NOZZLE.to_array(),
         normal: [0.0, 0.0, -1.0],
         uv: [0.5, 0.0],
         planet_id: 2,
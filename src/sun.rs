@@ -0,0 +1,319 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use glam::Vec3;
+use crate::hdr::HDR_FORMAT;
+
+// Estado del map_async de la lectura de oclusión
+const MAP_PENDING: u8 = 0;
+const MAP_OK: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// Puntos de prueba repartidos sobre el disco del sol, uno por consulta de
+/// oclusión. Se cuentan como visibles/ocultos (en GL el resultado es binario)
+/// y la fracción visible atenúa el destello.
+pub const SUN_PROBES: u32 = 25;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SunUniforms {
+    center: [f32; 4], // w = radio
+    color: [f32; 4],  // w = intensidad del destello
+    corona: [f32; 4], // x = extensión en radios solares, y = visibilidad (0..1)
+}
+
+/// Corona alrededor del sol y destello de lente que se apaga cuando un
+/// planeta lo tapa. La visibilidad se mide con consultas de oclusión dentro
+/// del pase principal y se lee de vuelta uno o dos frames después.
+pub struct SunEffects {
+    pub corona_extent: f32,
+    pub flare_intensity: f32,
+    visibility: f32, // suavizada en el tiempo
+    target_visibility: f32,
+    uniform_buf: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    corona_pipeline: wgpu::RenderPipeline,
+    probe_pipeline: wgpu::RenderPipeline,
+    flare_pipeline: wgpu::RenderPipeline,
+    pub queries: wgpu::QuerySet,
+    resolve_buf: wgpu::Buffer,
+    readback_buf: wgpu::Buffer,
+    copy_pending: bool, // se copió al buffer de lectura en este frame
+    mapping: bool,      // hay un map_async en curso o el buffer sigue mapeado
+    map_state: Arc<AtomicU8>,
+}
+
+impl SunEffects {
+    pub fn new(device: &wgpu::Device, globals_layout: &wgpu::BindGroupLayout, sample_count: u32) -> Self {
+        let uniform_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sun Uniforms"),
+            size: std::mem::size_of::<SunUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bg_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Sun Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Sun BG"),
+            layout: &bg_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buf.as_entire_binding(),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shader_sun.wgsl"));
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sun Pipeline Layout"),
+            bind_group_layouts: &[globals_layout, &bg_layout],
+            push_constant_ranges: &[],
+        });
+
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::OVER,
+        };
+
+        // Pipelines del pase principal: mismo formato, depth y MSAA que la escena
+        let scene_pipeline = |label, vs, fs, blend, write_mask, compare| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: vs,
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: fs,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: HDR_FORMAT,
+                        blend,
+                        write_mask,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: false,
+                    depth_compare: compare,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        // Corona: con depth test para que los planetas la tapen
+        let corona_pipeline = scene_pipeline(
+            "Corona Pipeline",
+            "vs_corona",
+            "fs_corona",
+            Some(additive),
+            wgpu::ColorWrites::ALL,
+            wgpu::CompareFunction::Less,
+        );
+
+        // Sondas de oclusión: solo depth test, no escriben color
+        let probe_pipeline = scene_pipeline(
+            "Sun Probe Pipeline",
+            "vs_probe",
+            "fs_probe",
+            None,
+            wgpu::ColorWrites::empty(),
+            wgpu::CompareFunction::LessEqual,
+        );
+
+        // Destello: pase aparte sobre la imagen HDR ya resuelta
+        let flare_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Lens Flare Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_flare",
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_flare",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(additive),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let queries = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Sun Occlusion Queries"),
+            ty: wgpu::QueryType::Occlusion,
+            count: SUN_PROBES,
+        });
+
+        let results_size = (SUN_PROBES as usize * std::mem::size_of::<u64>()) as wgpu::BufferAddress;
+        let resolve_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sun Query Resolve"),
+            size: results_size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sun Query Readback"),
+            size: results_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            corona_extent: 3.0,
+            flare_intensity: 1.0,
+            visibility: 0.0,
+            target_visibility: 0.0,
+            uniform_buf,
+            bind_group,
+            corona_pipeline,
+            probe_pipeline,
+            flare_pipeline,
+            queries,
+            resolve_buf,
+            readback_buf,
+            copy_pending: false,
+            mapping: false,
+            map_state: Arc::new(AtomicU8::new(MAP_PENDING)),
+        }
+    }
+
+    /// Lee los resultados de oclusión si ya llegaron y sube los uniforms
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, center: Vec3, radius: f32, dt: f32) {
+        device.poll(wgpu::Maintain::Poll);
+        match self.map_state.load(Ordering::Acquire) {
+            MAP_OK if self.mapping => {
+                {
+                    let data = self.readback_buf.slice(..).get_mapped_range();
+                    let results: &[u64] = bytemuck::cast_slice(&data);
+                    let visible = results.iter().filter(|&&samples| samples > 0).count();
+                    self.target_visibility = visible as f32 / SUN_PROBES as f32;
+                }
+                self.readback_buf.unmap();
+                self.mapping = false;
+            }
+            // El buffer no llegó a mapearse: se vuelve a resolver en el
+            // siguiente frame y la visibilidad se queda como estaba mientras
+            MAP_FAILED if self.mapping => {
+                self.mapping = false;
+                self.copy_pending = false;
+            }
+            _ => {}
+        }
+
+        // Suavizado para ocultar la latencia y el salto entre sondas
+        let k = 1.0 - (-dt * 12.0).exp();
+        self.visibility += (self.target_visibility - self.visibility) * k;
+
+        let uniforms = SunUniforms {
+            center: [center.x, center.y, center.z, radius],
+            color: [4.0, 2.2, 0.8, self.flare_intensity],
+            corona: [self.corona_extent, self.visibility, 0.0, 0.0],
+        };
+        queue.write_buffer(&self.uniform_buf, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    pub fn draw_corona<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, globals_bg: &'a wgpu::BindGroup) {
+        pass.set_pipeline(&self.corona_pipeline);
+        pass.set_bind_group(0, globals_bg, &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.draw(0..6, 0..1);
+    }
+
+    /// Dibuja las sondas dentro del pase principal (creado con
+    /// `occlusion_query_set: Some(&queries)`), después de los opacos
+    pub fn draw_probes<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, globals_bg: &'a wgpu::BindGroup) {
+        pass.set_pipeline(&self.probe_pipeline);
+        pass.set_bind_group(0, globals_bg, &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        for i in 0..SUN_PROBES {
+            pass.begin_occlusion_query(i);
+            pass.draw(0..6, i..i + 1);
+            pass.end_occlusion_query();
+        }
+    }
+
+    /// Copia los resultados de las consultas al buffer de lectura, salvo que
+    /// el del frame anterior aún no se haya leído
+    pub fn resolve_queries(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.mapping {
+            return;
+        }
+        encoder.resolve_query_set(&self.queries, 0..SUN_PROBES, &self.resolve_buf, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buf, 0, &self.readback_buf, 0, self.resolve_buf.size());
+        self.copy_pending = true;
+    }
+
+    /// Pide el mapeo del buffer de lectura; llamar después de `queue.submit`
+    pub fn request_readback(&mut self) {
+        if !self.copy_pending {
+            return;
+        }
+        self.copy_pending = false;
+        self.mapping = true;
+        let state = self.map_state.clone();
+        state.store(MAP_PENDING, Ordering::Release);
+        self.readback_buf.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            state.store(if result.is_ok() { MAP_OK } else { MAP_FAILED }, Ordering::Release);
+        });
+    }
+
+    /// Pase del destello sobre `hdr_view`; debe ir después del pase principal
+    pub fn draw_flare(&self, encoder: &mut wgpu::CommandEncoder, globals_bg: &wgpu::BindGroup, hdr_view: &wgpu::TextureView) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Lens Flare Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: hdr_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.flare_pipeline);
+        pass.set_bind_group(0, globals_bg, &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.draw(0..6, 0..7); // una instancia por elemento del destello
+    }
+}
@@ -0,0 +1,156 @@
+use bytemuck::Zeroable;
+
+/// Cuerpos como máximo con superficie procedural (índice = planet_id)
+pub const MAX_SURFACES: usize = 16;
+
+/// Generador de color que usa el shader para un cuerpo
+#[derive(Clone, Copy, PartialEq)]
+pub enum SurfaceKind {
+    Star,        // plasma emisivo animado
+    Terrestrial, // continentes, océanos, casquetes y nubes
+    GasGiant,    // bandas deformadas por turbulencia
+    Hull,        // metal por UV (la nave no es una esfera)
+}
+
+/// Superficie procedural de un cuerpo. El ruido se evalúa sobre la normal en
+/// espacio de objeto, así que no hay costuras en el cierre de la UV.
+#[derive(Clone, Copy)]
+pub struct Surface {
+    pub body: usize, // índice del cuerpo (= planet_id de sus vértices)
+    pub kind: SurfaceKind,
+    pub seed: f32,
+    pub frequency: f32,      // escala de los continentes o de las bandas
+    pub octaves: u32,        // octavas del ruido principal; el resto se deriva de aquí
+    pub sea_level: f32,      // -1 = sin océanos, 1 = todo océano
+    pub warp: f32,           // intensidad del domain warping
+    pub ridges: f32,         // peso del ruido ridged (cordilleras)
+    pub ice_caps: f32,       // |latitud| desde la que hay hielo (0..1, 1 = sin casquetes)
+    pub clouds: f32,         // cobertura de la capa de nubes (0..1, 0 = sin capa)
+    pub cloud_height: f32,   // altura de la capa en fracción del radio
+    pub cloud_speed: f32,    // giro de las nubes respecto al suelo (rad/s)
+    pub ocean: [f32; 3],
+    pub land: [f32; 3],
+    pub highland: [f32; 3],
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Self {
+            body: 0,
+            kind: SurfaceKind::Terrestrial,
+            seed: 0.0,
+            frequency: 1.5,
+            octaves: 6,
+            sea_level: 0.0,
+            warp: 0.5,
+            ridges: 0.3,
+            ice_caps: 1.0,
+            clouds: 0.0,
+            cloud_height: 0.05,
+            cloud_speed: 0.02,
+            ocean: [0.05, 0.15, 0.4],
+            land: [0.25, 0.45, 0.15],
+            highland: [0.5, 0.42, 0.32],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SurfaceParams {
+    kind: u32,
+    seed: f32,
+    frequency: f32,
+    sea_level: f32,
+    warp: f32,
+    ridges: f32,
+    ice_caps: f32,
+    clouds: f32,
+    octaves: u32,
+    cloud_height: f32,
+    cloud_speed: f32,
+    _pad: u32,
+    ocean: [f32; 4],
+    land: [f32; 4],
+    highland: [f32; 4],
+}
+
+/// Parámetros de superficie en la GPU, leídos por el shader de los modelos
+/// y del terreno en el grupo 2 según el planet_id de cada vértice.
+pub struct GpuSurfaces {
+    buffer: wgpu::Buffer,
+    pub layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl GpuSurfaces {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Surface Params"),
+            size: (MAX_SURFACES * std::mem::size_of::<SurfaceParams>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Surface Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                // El terreno también la lee en el vertex shader para el relieve
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Surface BG"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self { buffer, layout, bind_group }
+    }
+
+    /// Sube la tabla completa; los cuerpos sin superficie quedan en blanco
+    pub fn upload(&self, queue: &wgpu::Queue, surfaces: &[Surface]) {
+        let mut params = [SurfaceParams::zeroed(); MAX_SURFACES];
+        for s in surfaces {
+            if s.body >= MAX_SURFACES {
+                println!("Superficie del cuerpo {} ignorada: la tabla admite {} cuerpos", s.body, MAX_SURFACES);
+                continue;
+            }
+            let rgb = |c: [f32; 3]| [c[0], c[1], c[2], 1.0];
+            params[s.body] = SurfaceParams {
+                kind: match s.kind {
+                    SurfaceKind::Star => 0,
+                    SurfaceKind::Terrestrial => 1,
+                    SurfaceKind::GasGiant => 2,
+                    SurfaceKind::Hull => 3,
+                },
+                seed: s.seed,
+                frequency: s.frequency,
+                sea_level: s.sea_level,
+                warp: s.warp,
+                ridges: s.ridges,
+                ice_caps: s.ice_caps,
+                clouds: s.clouds,
+                octaves: s.octaves,
+                cloud_height: s.cloud_height,
+                cloud_speed: s.cloud_speed,
+                _pad: 0,
+                ocean: rgb(s.ocean),
+                land: rgb(s.land),
+                highland: rgb(s.highland),
+            };
+        }
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&params));
+    }
+}
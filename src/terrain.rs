@@ -0,0 +1,342 @@
+use std::ops::Range;
+use glam::Vec3;
+use wgpu::util::DeviceExt;
+use crate::mesh::cube_to_sphere;
+
+/// Celdas por lado de cada chunk; todos comparten la misma rejilla
+pub const CHUNK_RESOLUTION: u32 = 16;
+
+/// Chunks como máximo entre todos los cuerpos en un frame
+pub const MAX_CHUNKS: usize = 4096;
+
+/// Origen de la altura del relieve
+#[derive(Clone, Copy)]
+pub enum HeightSource {
+    Noise,                  // el mismo ruido que colorea la superficie
+    Heightmap(&'static str), // PNG equirectangular en escala de grises
+}
+
+/// Relieve desplazado sobre una cube-sphere con LOD por quadtree. Cada cara
+/// del cubo es la raíz de un quadtree; un nodo se divide cuando la cámara
+/// está a menos de `split_distance` veces su tamaño.
+#[derive(Clone, Copy)]
+pub struct Terrain {
+    pub body: usize,
+    pub source: HeightSource,
+    pub amplitude: f32,      // altura máxima en fracción del radio
+    pub max_depth: u32,      // niveles de subdivisión
+    pub split_distance: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TerrainVertex {
+    st: [f32; 2], // posición dentro del chunk (0..1)
+    skirt: f32,   // 1 = vértice del faldón que tapa las grietas entre niveles
+}
+
+impl TerrainVertex {
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TerrainVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                0 => Float32x2,  // st
+                1 => Float32     // skirt
+            ])),
+        }
+    }
+}
+
+/// Un nodo hoja del quadtree: región de una cara del cubo
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ChunkInstance {
+    face: u32,
+    origin: [f32; 2], // esquina en coordenadas de la cara (0..1)
+    size: f32,        // lado en coordenadas de la cara
+}
+
+impl ChunkInstance {
+    fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ChunkInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                2 => Uint32,     // face
+                3 => Float32x2,  // origin
+                4 => Float32     // size
+            ])),
+        }
+    }
+
+    /// Layouts de vértice del pipeline de terreno: rejilla + instancias
+    pub fn buffer_layouts() -> [wgpu::VertexBufferLayout<'static>; 2] {
+        [TerrainVertex::layout(), ChunkInstance::layout()]
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct TerrainUniforms {
+    center: [f32; 4], // w = radio
+    params: [f32; 4], // amplitud, 1.0 si usa heightmap, cuerpo, sin usar
+}
+
+struct TerrainBody {
+    uniform_buf: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    heightmap: bool,    // false si se usa ruido (o el PNG no cargó)
+    chunks: Range<u32>, // instancias de este frame
+}
+
+/// Terreno de todos los cuerpos con relieve. Cada frame se recorre el
+/// quadtree de cada cuerpo desde la cámara y las hojas visibles se suben
+/// como instancias de una única rejilla con faldones.
+pub struct GpuTerrain {
+    grid_vb: wgpu::Buffer,
+    grid_ib: wgpu::Buffer,
+    grid_icount: u32,
+    instance_buf: wgpu::Buffer,
+    bodies: Vec<TerrainBody>,
+}
+
+impl GpuTerrain {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        terrains: &[Terrain],
+    ) -> Self {
+        let (verts, inds) = Self::chunk_grid(CHUNK_RESOLUTION);
+        let grid_vb = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Terrain Grid VB"),
+            contents: bytemuck::cast_slice(&verts),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let grid_ib = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Terrain Grid IB"),
+            contents: bytemuck::cast_slice(&inds),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let instance_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Terrain Chunks"),
+            size: (MAX_CHUNKS * std::mem::size_of::<ChunkInstance>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Heightmap Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bodies = terrains
+            .iter()
+            .map(|terrain| {
+                let uniform_buf = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Terrain Uniforms"),
+                    size: std::mem::size_of::<TerrainUniforms>() as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+
+                // Sin heightmap se enlaza una textura de 1x1 que el shader no lee
+                let (heightmap, (width, height, data)) = match terrain.source {
+                    HeightSource::Heightmap(path) => match load_heightmap(path) {
+                        Ok(image) => (true, image),
+                        Err(e) => {
+                            println!("No se pudo cargar {} ({}), usando ruido", path, e);
+                            (false, (1, 1, vec![0]))
+                        }
+                    },
+                    HeightSource::Noise => (false, (1, 1, vec![0])),
+                };
+                let texture = device.create_texture_with_data(
+                    queue,
+                    &wgpu::TextureDescriptor {
+                        label: Some("Heightmap"),
+                        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: wgpu::TextureFormat::R8Unorm,
+                        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                        view_formats: &[],
+                    },
+                    wgpu::util::TextureDataOrder::LayerMajor,
+                    &data,
+                );
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Terrain BG"),
+                    layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: uniform_buf.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(&view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::Sampler(&sampler),
+                        },
+                    ],
+                });
+
+                TerrainBody { uniform_buf, bind_group, heightmap, chunks: 0..0 }
+            })
+            .collect();
+
+        Self {
+            grid_vb,
+            grid_ib,
+            grid_icount: inds.len() as u32,
+            instance_buf,
+            bodies,
+        }
+    }
+
+    /// Rejilla de (res+1)² vértices más un faldón por cada borde
+    fn chunk_grid(res: u32) -> (Vec<TerrainVertex>, Vec<u32>) {
+        let row = res + 1;
+        let mut verts = Vec::with_capacity((row * row + 4 * row) as usize);
+        for j in 0..=res {
+            for i in 0..=res {
+                let st = [i as f32 / res as f32, j as f32 / res as f32];
+                verts.push(TerrainVertex { st, skirt: 0.0 });
+            }
+        }
+
+        let mut inds = Vec::with_capacity((res * res * 6 + 4 * res * 6) as usize);
+        for j in 0..res {
+            for i in 0..res {
+                let a = j * row + i;
+                inds.extend_from_slice(&[a, a + 1, a + row, a + 1, a + row + 1, a + row]);
+            }
+        }
+
+        // Bordes recorridos como índices de la rejilla: abajo, arriba, izquierda, derecha
+        let edges: [Vec<u32>; 4] = [
+            (0..row).collect(),
+            (0..row).map(|i| res * row + i).collect(),
+            (0..row).map(|j| j * row).collect(),
+            (0..row).map(|j| j * row + res).collect(),
+        ];
+        for edge in edges {
+            let base = verts.len() as u32;
+            for &k in &edge {
+                verts.push(TerrainVertex { st: verts[k as usize].st, skirt: 1.0 });
+            }
+            for n in 0..res {
+                let (a, b) = (edge[n as usize], edge[n as usize + 1]);
+                let (sa, sb) = (base + n, base + n + 1);
+                inds.extend_from_slice(&[a, b, sa, b, sb, sa]);
+            }
+        }
+
+        (verts, inds)
+    }
+
+    /// Recorre los quadtrees desde `eye` y sube chunks y parámetros
+    pub fn update(&mut self, queue: &wgpu::Queue, terrains: &[Terrain], positions: &[(Vec3, f32)], eye: Vec3) {
+        let mut chunks = Vec::new();
+        for (body, terrain) in self.bodies.iter_mut().zip(terrains) {
+            let (center, radius) = positions[terrain.body];
+            let start = chunks.len() as u32;
+            for face in 0..6 {
+                select_chunks(terrain, center, radius, eye, face, [0.0, 0.0], 1.0, 0, &mut chunks);
+            }
+            chunks.truncate(MAX_CHUNKS);
+            body.chunks = start.min(chunks.len() as u32)..chunks.len() as u32;
+
+            let uniforms = TerrainUniforms {
+                center: [center.x, center.y, center.z, radius],
+                params: [terrain.amplitude, if body.heightmap { 1.0 } else { 0.0 }, terrain.body as f32, 0.0],
+            };
+            queue.write_buffer(&body.uniform_buf, 0, bytemuck::bytes_of(&uniforms));
+        }
+        if !chunks.is_empty() {
+            queue.write_buffer(&self.instance_buf, 0, bytemuck::cast_slice(&chunks));
+        }
+    }
+
+    /// Dibuja el terreno con el pipeline de terreno ya fijado
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        pass.set_vertex_buffer(0, self.grid_vb.slice(..));
+        pass.set_vertex_buffer(1, self.instance_buf.slice(..));
+        pass.set_index_buffer(self.grid_ib.slice(..), wgpu::IndexFormat::Uint32);
+        for body in &self.bodies {
+            if body.chunks.is_empty() {
+                continue;
+            }
+            pass.set_bind_group(3, &body.bind_group, &[]);
+            pass.draw_indexed(0..self.grid_icount, 0, body.chunks.clone());
+        }
+    }
+}
+
+/// Baja por el quadtree de una cara: descarta los nodos tras el horizonte y
+/// divide los cercanos hasta `max_depth`
+#[allow(clippy::too_many_arguments)]
+fn select_chunks(
+    terrain: &Terrain,
+    center: Vec3,
+    radius: f32,
+    eye: Vec3,
+    face: usize,
+    origin: [f32; 2],
+    size: f32,
+    depth: u32,
+    out: &mut Vec<ChunkInstance>,
+) {
+    let dir = cube_to_sphere(face, origin[0] + size * 0.5, origin[1] + size * 0.5).normalize();
+    let to_eye = eye - center;
+    let dist = to_eye.length();
+
+    // Horizonte: el nodo (de radio angular ~size·π/4·√2) debe asomar por
+    // el casquete visible desde la cámara
+    let top = radius * (1.0 + terrain.amplitude);
+    if dist > top {
+        let horizon = (radius / dist).clamp(-1.0, 1.0).acos();
+        let node_radius = size * std::f32::consts::FRAC_PI_4 * std::f32::consts::SQRT_2;
+        let angle = dir.dot(to_eye / dist).clamp(-1.0, 1.0).acos();
+        if angle > horizon + node_radius {
+            return;
+        }
+    }
+
+    // Longitud aproximada del nodo sobre la esfera (una cara abarca ~π/2)
+    let length = size * std::f32::consts::FRAC_PI_2 * radius;
+    let node_center = center + dir * radius;
+    if depth < terrain.max_depth && eye.distance(node_center) < terrain.split_distance * length {
+        let half = size * 0.5;
+        for (dx, dy) in [(0.0, 0.0), (half, 0.0), (0.0, half), (half, half)] {
+            let child = [origin[0] + dx, origin[1] + dy];
+            select_chunks(terrain, center, radius, eye, face, child, half, depth + 1, out);
+        }
+    } else {
+        out.push(ChunkInstance { face: face as u32, origin, size });
+    }
+}
+
+/// Lee un PNG en escala de grises (8 bits) o toma el canal rojo si es RGB(A)
+fn load_heightmap(path: &str) -> Result<(u32, u32, Vec<u8>), Box<dyn std::error::Error>> {
+    let mut decoder = png::Decoder::new(std::fs::File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    let channels = info.color_type.samples();
+    let data = buf[..info.buffer_size()].iter().step_by(channels).copied().collect();
+    Ok((info.width, info.height, data))
+}
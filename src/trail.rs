@@ -0,0 +1,136 @@
+use glam::Vec3;
+
+/// Estela de un cuerpo: guarda en un buffer circular las últimas posiciones
+/// en mundo, muestreadas a una frecuencia fija.
+pub struct Trail {
+    pub body: usize, // índice en scene.planet_positions
+    pub color: [f32; 3],
+    pub sample_rate: f32, // muestras por segundo
+    samples: Vec<Vec3>,
+    head: usize, // siguiente posición a escribir
+    len: usize,
+    accum: f32,
+}
+
+impl Trail {
+    /// `length` es el número máximo de muestras guardadas.
+    pub fn new(body: usize, color: [f32; 3], length: usize, sample_rate: f32) -> Self {
+        Self {
+            body,
+            color,
+            sample_rate,
+            samples: vec![Vec3::ZERO; length.max(2)],
+            head: 0,
+            len: 0,
+            accum: 0.0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.accum = 0.0;
+    }
+
+    pub fn record(&mut self, dt: f32, pos: Vec3) {
+        self.accum += dt;
+        let interval = 1.0 / self.sample_rate;
+
+        // La primera muestra se toma de inmediato
+        if self.len > 0 && self.accum < interval {
+            return;
+        }
+        self.accum = (self.accum - interval).max(0.0);
+
+        self.samples[self.head] = pos;
+        self.head = (self.head + 1) % self.samples.len();
+        self.len = (self.len + 1).min(self.samples.len());
+    }
+
+    /// Muestras de la más reciente a la más antigua
+    fn iter_newest(&self) -> impl Iterator<Item = Vec3> + '_ {
+        let cap = self.samples.len();
+        (1..=self.len).map(move |i| self.samples[(self.head + cap - i) % cap])
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TrailVertex {
+    pub pos: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl TrailVertex {
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TrailVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: Box::leak(Box::new(wgpu::vertex_attr_array![
+                0 => Float32x3,  // pos
+                1 => Float32x4   // color
+            ])),
+        }
+    }
+}
+
+/// Buffer de vértices compartido por todas las estelas. Se dimensiona una vez
+/// para la capacidad total y cada frame solo se escribe la parte usada.
+pub struct GpuTrails {
+    vb: wgpu::Buffer,
+    vertex_count: u32,
+}
+
+impl GpuTrails {
+    pub fn new(device: &wgpu::Device, trails: &[Trail]) -> Self {
+        // Cada muestra aporta un segmento (2 vértices), más el segmento a la posición actual
+        let max_verts: usize = trails.iter().map(|t| t.capacity() * 2).sum();
+        let vb = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Trail VB"),
+            size: (max_verts.max(2) * std::mem::size_of::<TrailVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self { vb, vertex_count: 0 }
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, trails: &[Trail], positions: &[(Vec3, f32)]) {
+        let mut verts = Vec::new();
+
+        for trail in trails {
+            let cap = trail.capacity() as f32;
+            let fade = |age: f32| {
+                let a = 1.0 - age / cap;
+                [trail.color[0], trail.color[1], trail.color[2], a * a]
+            };
+
+            // Une la posición actual con la muestra más reciente para que no haya hueco
+            let mut prev = positions[trail.body].0;
+            let mut prev_color = fade(0.0);
+            for (age, p) in trail.iter_newest().enumerate() {
+                let color = fade(age as f32 + 1.0);
+                verts.push(TrailVertex { pos: prev.to_array(), color: prev_color });
+                verts.push(TrailVertex { pos: p.to_array(), color });
+                prev = p;
+                prev_color = color;
+            }
+        }
+
+        self.vertex_count = verts.len() as u32;
+        if !verts.is_empty() {
+            queue.write_buffer(&self.vb, 0, bytemuck::cast_slice(&verts));
+        }
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        pass.set_vertex_buffer(0, self.vb.slice(..));
+        pass.draw(0..self.vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,257 @@
+use glam::{Vec2, Vec3};
+use crate::renderer::{Renderer, Globals};
+use crate::camera::{Camera, CollisionSphere, CAMERA_COLLISION_RADIUS};
+use crate::scene::{Scene, SUN_MU};
+use crate::orbit::GpuOrbits;
+use crate::trail::GpuTrails;
+use crate::asteroids::GpuAsteroids;
+use crate::comets::GpuComets;
+use crate::particles::{Emitter, DEBRIS, EXHAUST, SOLAR_WIND};
+use crate::atmosphere::GpuAtmospheres;
+use crate::clouds::GpuClouds;
+use crate::terrain::GpuTerrain;
+use crate::spaceship;
+
+/// Distancia al cinturón a partir de la cual se leen las rocas para colisiones
+const BELT_COLLISION_MARGIN: f32 = 6.0;
+/// Partículas de restos por cada choque del modo N-cuerpos
+const DEBRIS_PARTICLES: u32 = 300;
+
+/// La escena y lo que se sube a la GPU para dibujarla. La ventana y el modo
+/// sin ventana (headless.rs) avanzan y dibujan los frames igual.
+pub struct World {
+    pub scene: Scene,
+    pub asteroids: GpuAsteroids,
+    orbit_lines: GpuOrbits,
+    trail_lines: GpuTrails,
+    atmospheres: GpuAtmospheres,
+    comets: GpuComets,
+    exhaust: Emitter,
+    solar_wind: Emitter,
+    clouds: GpuClouds,
+    terrain: GpuTerrain,
+
+    time: f32,
+    dt: f32, // paso del último update
+    ship_velocity: Vec3,
+    near_belt: bool,
+}
+
+impl World {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, renderer: &Renderer, scene_path: &str) -> Self {
+        let mut scene = Scene::load_models(device);
+        scene.load_nbody(scene_path);
+        renderer.surfaces.upload(queue, &scene.surfaces);
+        Self {
+            orbit_lines: GpuOrbits::new(device, &renderer.orbit_bg_layout, &scene.orbits),
+            trail_lines: GpuTrails::new(device, &scene.trails),
+            atmospheres: GpuAtmospheres::new(device, &renderer.atmosphere_bg_layout, &scene.atmospheres),
+            asteroids: GpuAsteroids::new(device, SUN_MU, &scene.belt),
+            comets: GpuComets::new(device, SUN_MU, &scene.comets),
+            exhaust: Emitter::new(EXHAUST),
+            solar_wind: Emitter::new(SOLAR_WIND),
+            clouds: GpuClouds::new(device, &scene.surfaces, scene.planet_positions.len()),
+            terrain: GpuTerrain::new(device, queue, &renderer.terrain_bg_layout, &scene.terrains),
+            scene,
+
+            time: 0.0,
+            dt: 0.0,
+            ship_velocity: Vec3::ZERO,
+            near_belt: false,
+        }
+    }
+
+    /// Avanza la simulación, la nave y la cámara `dt` segundos. `shadows` dice
+    /// si este frame se dibujan sombras (ver `Scene::update`).
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, cam: &mut Camera, dt: f32, mouse_delta: Vec2, shadows: bool) {
+        self.time += dt;
+        self.dt = dt;
+        let scene = &mut self.scene;
+        scene.update(self.time, dt, queue, shadows);
+
+        // Actualizar rotación de la nave en órbita con flechas
+        self.ship_velocity = cam.update_player_ship(dt, mouse_delta, &mut scene.planet_positions[2].0, &mut scene.ship_rot);
+        scene.record_trails(dt);
+
+        // Convertir posiciones de planetas a esferas de colisión
+        let mut collision_spheres: Vec<CollisionSphere> = scene.planet_positions
+            .iter()
+            .map(|(pos, radius)| CollisionSphere { center: *pos, radius: *radius })
+            .collect();
+
+        // Las rocas solo cuentan cerca del cinturón: su posición vive en
+        // la GPU y leerla cada frame no sale gratis
+        let probe = cam.collision_point(scene.planet_positions[2].0, scene.ship_rot);
+        let probe_radius = probe.length();
+        self.near_belt = probe_radius > scene.belt.inner_radius - BELT_COLLISION_MARGIN
+            && probe_radius < scene.belt.outer_radius + BELT_COLLISION_MARGIN;
+        if self.near_belt {
+            collision_spheres.extend(
+                self.asteroids.propagator
+                    .positions(device)
+                    .iter()
+                    .filter(|p| {
+                        // Una roca que ya nos alcanzó no bloquea: si no, la
+                        // cámara quedaría atrapada dentro de ella
+                        let d = p.truncate().distance(probe);
+                        d < BELT_COLLISION_MARGIN && d > p.w + CAMERA_COLLISION_RADIUS
+                    })
+                    .map(|p| CollisionSphere { center: p.truncate(), radius: p.w }),
+            );
+        }
+
+        cam.update_from_input(dt, mouse_delta, &collision_spheres);
+    }
+
+    /// Dibuja el frame en `target`, del formato de salida del renderer y de
+    /// tamaño `size`
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        renderer: &mut Renderer,
+        cam: &Camera,
+        target: &wgpu::TextureView,
+        size: (u32, u32),
+    ) {
+        let (time, dt) = (self.time, self.dt);
+        let scene = &mut self.scene;
+        let (width, height) = (size.0 as f32, size.1 as f32);
+
+        let eye = cam.eye(scene.planet_positions[2].0, scene.ship_rot);
+        let globals = Globals {
+            view_proj: cam.view_proj(scene.planet_positions[2].0, scene.ship_rot, width / height).to_cols_array_2d(),
+            time,
+            _pad0: [0.0; 3],
+            viewport: [width, height, 1.0 / width, 1.0 / height],
+            camera_pos: eye.extend(1.0).to_array(),
+            _pad3: [0.0; 4],
+        };
+        queue.write_buffer(&renderer.globals_buf, 0, bytemuck::bytes_of(&globals));
+
+        self.orbit_lines.sync(queue, &scene.orbits, &scene.planet_positions);
+        self.trail_lines.update(queue, &scene.trails, &scene.planet_positions);
+        self.atmospheres.sync(queue, &scene.atmospheres, &scene.planet_positions);
+        self.clouds.sync(queue, &scene.planet_positions);
+        self.terrain.update(queue, &scene.terrains, &scene.planet_positions, eye);
+        let (sun_pos, sun_radius) = scene.planet_positions[0];
+        renderer.sun.update(device, queue, sun_pos, sun_radius, dt);
+        self.comets.update(queue, time, sun_pos);
+        renderer.shadows.update(queue, sun_pos);
+
+        /* ------ Emisión de partículas ------ */
+        let (ship_pos, ship_radius) = scene.planet_positions[2];
+        let thrust = cam.thrust_level();
+        if thrust > 0.0 {
+            // Con el gatillo a medias sale menos chorro
+            let count = self.exhaust.count(dt * thrust);
+            // El chorro sale hacia atrás según hacia dónde vuela la nave
+            let orientation = spaceship::orientation(Camera::ship_forward(scene.ship_rot));
+            let nozzle = ship_pos + spaceship::nozzle(ship_radius, orientation);
+            renderer.particles.emit(queue, &self.exhaust.def, count, nozzle, ship_radius * 0.05, orientation * spaceship::NOZZLE_DIR, self.ship_velocity);
+        }
+        let count = self.solar_wind.count(dt);
+        renderer.particles.emit(queue, &self.solar_wind.def, count, sun_pos, sun_radius, Vec3::Y, Vec3::ZERO);
+        for hit in scene.take_collisions() {
+            renderer.particles.emit(queue, &DEBRIS, DEBRIS_PARTICLES, hit.position, hit.radius, Vec3::Y, hit.velocity);
+        }
+        // La nave no cuenta: el chorro sale de dentro de su esfera
+        let occluders: Vec<(Vec3, f32)> = scene.planet_positions
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != 2)
+            .map(|(_, &body)| body)
+            .collect();
+        renderer.particles.set_occluders(queue, &occluders);
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        /* ------ Cinturón de asteroides (compute) ------ */
+        self.asteroids.propagator.update(queue, &mut encoder, time, dt);
+
+        /* ------ Partículas (compute) ------ */
+        renderer.particles.update(queue, &mut encoder, dt);
+        if self.near_belt {
+            self.asteroids.propagator.copy_readback(&mut encoder);
+        }
+
+        /* ------ Sombras desde el sol (el modelo 0 es el propio sol) ------ */
+        renderer.shadows.render(&mut encoder, &scene.models[1..]);
+
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Main Pass"),
+                color_attachments: &[Some(renderer.main_color_attachment(
+                    wgpu::Color {
+                        r: 0.0,
+                        g: 0.0,
+                        b: 0.0,
+                        a: 1.0,
+                    },
+                ))],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &renderer.depth_texture,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: Some(&renderer.sun.queries),
+            });
+
+            /* ------ Dibujar skybox/fondo con estrellas ------ */
+            renderer.draw_skybox(&mut pass);
+
+            /* ------ Dibujar planetas / modelos ------ */
+            for (i, model) in scene.models.iter().enumerate() {
+                if !scene.has_terrain(i) {
+                    renderer.draw_mesh(&mut pass, &model.vb, &model.ib, model.icount);
+                }
+            }
+
+            /* ------ Terreno con LOD (sustituye a la malla de esos cuerpos) ------ */
+            renderer.draw_terrain(&mut pass, &self.terrain);
+
+            /* ------ Cinturón de asteroides (instanciado) ------ */
+            renderer.draw_asteroids(&mut pass, &self.asteroids);
+            renderer.draw_comet_nuclei(&mut pass, &self.comets);
+
+            /* ------ Visibilidad del sol para el destello ------ */
+            renderer.draw_sun_probes(&mut pass);
+
+            /* ------ Nubes (translúcidas, bajo la atmósfera) ------ */
+            renderer.draw_clouds(&mut pass, &self.clouds);
+
+            /* ------ Atmósferas (translúcidas, tras los opacos) ------ */
+            renderer.draw_atmospheres(&mut pass, &self.atmospheres);
+
+            /* ------ Colas de los cometas (aditivas) ------ */
+            renderer.draw_comet_tails(&mut pass, &self.comets);
+
+            /* ------ Propulsor, restos y viento solar (aditivos) ------ */
+            renderer.draw_particles(&mut pass);
+
+            /* ------ Corona del sol (aditiva, tras los opacos) ------ */
+            renderer.draw_corona(&mut pass);
+
+            /* ------ Dibujar órbitas (no aplican en modo N-cuerpos) ------ */
+            if !scene.nbody_active() {
+                renderer.draw_orbits(&mut pass, &self.orbit_lines);
+            }
+
+            /* ------ Dibujar estelas ------ */
+            renderer.draw_trails(&mut pass, &self.trail_lines);
+        }
+        renderer.sun.resolve_queries(&mut encoder);
+
+        /* ------ Bloom + tone mapping + efectos al destino ------ */
+        renderer.post_process(queue, &mut encoder, target, time);
+
+        queue.submit(Some(encoder.finish()));
+        renderer.sun.request_readback();
+        self.asteroids.propagator.request_readback();
+    }
+}